@@ -0,0 +1,171 @@
+//! Compound conditions.
+//!
+//! Condition expressions are opaque to policies and interpreted only by an
+//! [Environment]. This module provides a [logical expression](LogicExp) over any
+//! condition type and an [environment](LogicEnvironment) that evaluates it using an
+//! environment for the underlying conditions.
+
+use std::borrow::Borrow;
+
+use crate::environment::Environment;
+
+/// Logical combination of conditions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LogicExp<C> {
+    /// A condition evaluated by the underlying environment.
+    Leaf(C),
+    /// Holds when both expressions hold.
+    And(Box<LogicExp<C>>, Box<LogicExp<C>>),
+    /// Holds when either expression holds.
+    Or(Box<LogicExp<C>>, Box<LogicExp<C>>),
+    /// Holds when the expression does not.
+    Not(Box<LogicExp<C>>),
+    /// Holds, or does not, irrespective of the environment.
+    Const(bool),
+}
+
+impl<C> From<C> for LogicExp<C> {
+    fn from(c: C) -> Self {
+        LogicExp::Leaf(c)
+    }
+}
+
+impl<C> LogicExp<C> {
+    pub fn and(self, r: LogicExp<C>) -> Self {
+        LogicExp::And(Box::new(self), Box::new(r))
+    }
+
+    pub fn or(self, r: LogicExp<C>) -> Self {
+        LogicExp::Or(Box::new(self), Box::new(r))
+    }
+}
+
+impl<C> std::ops::Not for LogicExp<C> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        LogicExp::Not(Box::new(self))
+    }
+}
+
+/// Environment for logical expressions. Leaves are evaluated by the inner environment.
+/// Every leaf of an expression is evaluated; `And` and `Or` do not short-circuit so
+/// that evaluation has the same effect on the inner environment regardless of outcome.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct LogicEnvironment<E>(pub E);
+
+impl<E> LogicEnvironment<E>
+where
+    E: Environment,
+{
+    fn eval(&self, exp: &LogicExp<E::CExp>) -> bool {
+        match exp {
+            LogicExp::Leaf(c) => self.0.evaluate(c),
+            LogicExp::And(l, r) => self.eval(l) & self.eval(r),
+            LogicExp::Or(l, r) => self.eval(l) | self.eval(r),
+            LogicExp::Not(e) => !self.eval(e),
+            LogicExp::Const(b) => *b,
+        }
+    }
+}
+
+impl<E> Environment for LogicEnvironment<E>
+where
+    E: Environment,
+{
+    type CExp = LogicExp<E::CExp>;
+
+    fn evaluate<Exp>(&self, exp: Exp) -> bool
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        self.eval(exp.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Conditions hold for even numbers. Counts evaluations.
+    #[derive(Default)]
+    struct EvenEnv(Cell<usize>);
+
+    impl Environment for EvenEnv {
+        type CExp = u32;
+
+        fn evaluate<Exp>(&self, exp: Exp) -> bool
+        where
+            Exp: Borrow<Self::CExp>,
+        {
+            self.0.set(self.0.get() + 1);
+            exp.borrow() % 2 == 0
+        }
+    }
+
+    const T: u32 = 2;
+    const F: u32 = 1;
+
+    fn leaf(c: u32) -> LogicExp<u32> {
+        c.into()
+    }
+
+    #[test]
+    fn test_leaf() {
+        let env = LogicEnvironment(EvenEnv::default());
+
+        assert!(env.evaluate(leaf(T)));
+        assert!(!env.evaluate(leaf(F)));
+    }
+
+    #[test]
+    fn test_const() {
+        let env = LogicEnvironment(EvenEnv::default());
+
+        assert!(env.evaluate(LogicExp::Const(true)));
+        assert!(!env.evaluate(LogicExp::Const(false)));
+        assert_eq!(env.0 .0.get(), 0);
+    }
+
+    #[test]
+    fn test_and() {
+        let env = LogicEnvironment(EvenEnv::default());
+
+        assert!(env.evaluate(leaf(T).and(leaf(T))));
+        assert!(!env.evaluate(leaf(T).and(leaf(F))));
+        assert!(!env.evaluate(leaf(F).and(leaf(T))));
+        assert!(!env.evaluate(leaf(F).and(leaf(F))));
+    }
+
+    #[test]
+    fn test_or() {
+        let env = LogicEnvironment(EvenEnv::default());
+
+        assert!(env.evaluate(leaf(T).or(leaf(T))));
+        assert!(env.evaluate(leaf(T).or(leaf(F))));
+        assert!(env.evaluate(leaf(F).or(leaf(T))));
+        assert!(!env.evaluate(leaf(F).or(leaf(F))));
+    }
+
+    #[test]
+    fn test_not() {
+        let env = LogicEnvironment(EvenEnv::default());
+
+        assert!(!env.evaluate(!leaf(T)));
+        assert!(env.evaluate(!leaf(F)));
+        assert!(env.evaluate(!!leaf(T)));
+    }
+
+    #[test]
+    fn test_no_short_circuit() {
+        let env = LogicEnvironment(EvenEnv::default());
+
+        assert!(!env.evaluate(leaf(F).and(leaf(T))));
+        assert_eq!(env.0 .0.get(), 2);
+
+        assert!(env.evaluate(leaf(T).or(leaf(F).and(leaf(F)))));
+        assert_eq!(env.0 .0.get(), 5);
+    }
+}
@@ -30,8 +30,10 @@
 //!
 
 use std::borrow::Borrow;
+use std::collections::HashMap;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Definite authorization.
 pub enum Effect {
     /// Definitiely authorized.
@@ -40,6 +42,27 @@ pub enum Effect {
     DENY,
 }
 
+impl Effect {
+    /// The opposite effect.
+    pub fn negate(self) -> Effect {
+        match self {
+            Effect::ALLOW => Effect::DENY,
+            Effect::DENY => Effect::ALLOW,
+        }
+    }
+}
+
+impl From<bool> for Effect {
+    /// `true` allows, `false` denies.
+    fn from(allowed: bool) -> Self {
+        if allowed {
+            Effect::ALLOW
+        } else {
+            Effect::DENY
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
 /// Effect computed from a policy evaluation. Adds an additional value representing that a policy
 /// evalutation does not apply under given conditions.
@@ -58,6 +81,22 @@ impl ComputedEffect {
     pub fn effect(&self) -> Option<Effect> {
         self.0
     }
+
+    /// Combine effects whose conditions have already been decided. Effects paired with
+    /// `false` are silent; the rest are combined as described in the [module](self)
+    /// documentation.
+    pub fn from_conditional(pairs: impl IntoIterator<Item = (Effect, bool)>) -> Self {
+        pairs
+            .into_iter()
+            .map(|(eff, holds)| if holds { eff.into() } else { SILENT })
+            .collect()
+    }
+
+    /// Determine if two computed effects are definite and opposed, i.e. one allows
+    /// and the other denies. Silence conflicts with nothing.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        matches!((*self, *other), (ALLOW, DENY) | (DENY, ALLOW))
+    }
 }
 
 impl<E> From<E> for ComputedEffect
@@ -99,10 +138,139 @@ where
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+/// Classification of a computed effect.
+pub enum DecisionKind {
+    /// The effect is [allow](ALLOW).
+    Allow,
+    /// The effect is [deny](DENY).
+    Deny,
+    /// The effect is [silence](SILENT).
+    Silent,
+}
+
+impl From<ComputedEffect> for DecisionKind {
+    fn from(ceff: ComputedEffect) -> Self {
+        match ceff.0 {
+            Some(Effect::ALLOW) => DecisionKind::Allow,
+            Some(Effect::DENY) => DecisionKind::Deny,
+            None => DecisionKind::Silent,
+        }
+    }
+}
+
+/// A computed effect together with obligations the enforcer must fulfil when acting on
+/// it, e.g. "log this access".
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ObligatedEffect<O> {
+    pub effect: ComputedEffect,
+    pub obligations: Vec<O>,
+}
+
+impl<O> ObligatedEffect<O> {
+    pub fn new(effect: ComputedEffect, obligations: Vec<O>) -> Self {
+        ObligatedEffect {
+            effect,
+            obligations,
+        }
+    }
+}
+
+impl<O> From<ComputedEffect> for ObligatedEffect<O> {
+    fn from(effect: ComputedEffect) -> Self {
+        ObligatedEffect::new(effect, Vec::new())
+    }
+}
+
+/// Effects are combined as computed effects are. The obligations of the effects that agree
+/// with the combined effect are concatenated in order; obligations of overridden allows
+/// and of silent effects are dropped.
+impl<O> FromIterator<ObligatedEffect<O>> for ObligatedEffect<O> {
+    fn from_iter<T: IntoIterator<Item = ObligatedEffect<O>>>(items: T) -> Self {
+        let items: Vec<ObligatedEffect<O>> = items.into_iter().collect();
+        let effect: ComputedEffect = items.iter().map(|item| item.effect).collect();
+        let obligations = if effect == SILENT {
+            Vec::new()
+        } else {
+            items
+                .into_iter()
+                .filter(|item| item.effect == effect)
+                .flat_map(|item| item.obligations)
+                .collect()
+        };
+        ObligatedEffect::new(effect, obligations)
+    }
+}
+
+/// Count computed effects by kind. Kinds that do not occur are absent from the result.
+pub fn group_by_effect(
+    effs: impl IntoIterator<Item = ComputedEffect>,
+) -> HashMap<DecisionKind, usize> {
+    let mut counts = HashMap::new();
+    for eff in effs {
+        *counts.entry(eff.into()).or_insert(0) += 1;
+    }
+    counts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn effect_from_bool() {
+        assert_eq!(Effect::from(true), Effect::ALLOW);
+        assert_eq!(Effect::from(false), Effect::DENY);
+    }
+
+    #[test]
+    fn effect_negate() {
+        assert_eq!(Effect::ALLOW.negate(), Effect::DENY);
+        assert_eq!(Effect::DENY.negate(), Effect::ALLOW);
+        assert_eq!(Effect::ALLOW.negate().negate(), Effect::ALLOW);
+        assert_eq!(Effect::DENY.negate().negate(), Effect::DENY);
+    }
+
+    #[test]
+    fn obligations_accumulate_on_allow() {
+        let actual: ObligatedEffect<&str> = [
+            ObligatedEffect::new(ALLOW, vec!["log"]),
+            ObligatedEffect::new(SILENT, vec!["notify"]),
+            ObligatedEffect::new(ALLOW, vec!["audit", "watermark"]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            actual,
+            ObligatedEffect::new(ALLOW, vec!["log", "audit", "watermark"])
+        );
+    }
+
+    #[test]
+    fn obligations_dropped_when_deny_overrides() {
+        let actual: ObligatedEffect<&str> = [
+            ObligatedEffect::new(ALLOW, vec!["log"]),
+            ObligatedEffect::new(DENY, vec!["alert"]),
+            ObligatedEffect::new(ALLOW, vec!["audit"]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(actual, ObligatedEffect::new(DENY, vec!["alert"]));
+    }
+
+    #[test]
+    fn obligations_silent() {
+        let actual: ObligatedEffect<&str> = [ObligatedEffect::new(SILENT, vec!["log"])]
+            .into_iter()
+            .collect();
+        assert_eq!(actual, SILENT.into());
+
+        let actual: ObligatedEffect<&str> = std::iter::empty().collect();
+        assert_eq!(actual, SILENT.into());
+    }
+
     #[test]
     fn collect_computed() {
         fn check<const N: usize>(effs: [ComputedEffect; N], expected: ComputedEffect) {
@@ -144,4 +312,58 @@ mod tests {
         check([SILENT, DENY, SILENT, ALLOW, SILENT], DENY);
         check([SILENT, ALLOW, SILENT, ALLOW, SILENT], ALLOW);
     }
+
+    #[test]
+    fn conflicts_with() {
+        assert!(!ALLOW.conflicts_with(&ALLOW));
+        assert!(ALLOW.conflicts_with(&DENY));
+        assert!(!ALLOW.conflicts_with(&SILENT));
+
+        assert!(DENY.conflicts_with(&ALLOW));
+        assert!(!DENY.conflicts_with(&DENY));
+        assert!(!DENY.conflicts_with(&SILENT));
+
+        assert!(!SILENT.conflicts_with(&ALLOW));
+        assert!(!SILENT.conflicts_with(&DENY));
+        assert!(!SILENT.conflicts_with(&SILENT));
+    }
+
+    #[test]
+    fn from_conditional() {
+        use Effect::{ALLOW as A, DENY as D};
+
+        assert_eq!(ComputedEffect::from_conditional([]), SILENT);
+        assert_eq!(
+            ComputedEffect::from_conditional([(A, false), (D, false)]),
+            SILENT
+        );
+        assert_eq!(
+            ComputedEffect::from_conditional([(A, true), (D, false)]),
+            ALLOW
+        );
+        assert_eq!(
+            ComputedEffect::from_conditional([(A, true), (D, true)]),
+            DENY
+        );
+        assert_eq!(
+            ComputedEffect::from_conditional([(A, false), (A, true), (D, false), (A, true)]),
+            ALLOW
+        );
+    }
+
+    #[test]
+    fn group_by_effect_counts() {
+        let counts = group_by_effect([ALLOW, DENY, SILENT, ALLOW, SILENT, ALLOW]);
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&DecisionKind::Allow], 3);
+        assert_eq!(counts[&DecisionKind::Deny], 1);
+        assert_eq!(counts[&DecisionKind::Silent], 2);
+
+        let counts = group_by_effect([DENY]);
+        assert_eq!(counts.get(&DecisionKind::Allow), None);
+        assert_eq!(counts[&DecisionKind::Deny], 1);
+
+        assert!(group_by_effect([]).is_empty());
+    }
 }
@@ -1,3 +1,12 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::effect::{ComputedEffect, SILENT};
+use crate::environment::Environment;
+use crate::matcher::Matcher;
+use crate::policy::{Assertion, Policy};
+use crate::principal::Authorized;
+
 pub struct Authorization<Subj>(Subj, bool);
 
 impl<Subj> Authorization<Subj> {
@@ -13,7 +22,7 @@ impl<Subj> Authorization<Subj> {
 pub struct Authorizations<Azn, Prin>(Prin, Vec<Azn>);
 
 impl<Subj, Prin> Authorizations<Authorization<Subj>, Prin> {
-    fn new<T: IntoIterator<Item = Authorization<Subj>>>(principal: Prin, items: T) -> Self {
+    pub fn new<T: IntoIterator<Item = Authorization<Subj>>>(principal: Prin, items: T) -> Self {
         Authorizations(principal, items.into_iter().collect())
     }
     pub fn authorized(&self) -> bool {
@@ -24,6 +33,16 @@ impl<Subj, Prin> Authorizations<Authorization<Subj>, Prin> {
         &self.0
     }
 
+    /// Authorizations whose subject was denied, in order.
+    pub fn denied(&self) -> impl Iterator<Item = &Authorization<Subj>> {
+        self.1.iter().filter(|azn| !azn.authorized())
+    }
+
+    /// Authorizations whose subject was allowed, in order.
+    pub fn allowed(&self) -> impl Iterator<Item = &Authorization<Subj>> {
+        self.1.iter().filter(|azn| azn.authorized())
+    }
+
     pub fn as_slice(&self) -> &[Authorization<Subj>] {
         self.1.as_slice()
     }
@@ -49,14 +68,308 @@ impl<Subj, Prin> IntoIterator for Authorizations<Authorization<Subj>, Prin> {
     }
 }
 
+/// Incrementally collects per-subject authorizations for a principal.
+pub struct AuthorizationsBuilder<Subj, Prin> {
+    principal: Prin,
+    items: Vec<Authorization<Subj>>,
+}
+
+impl<Subj, Prin> AuthorizationsBuilder<Subj, Prin> {
+    pub fn new(principal: Prin) -> Self {
+        AuthorizationsBuilder {
+            principal,
+            items: Vec::new(),
+        }
+    }
+
+    /// Record the authorization decision for a subject.
+    pub fn push(&mut self, subject: Subj, authorized: bool) -> &mut Self {
+        self.items.push(Authorization(subject, authorized));
+        self
+    }
+
+    pub fn build(self) -> Authorizations<Authorization<Subj>, Prin> {
+        Authorizations(self.principal, self.items)
+    }
+}
+
 pub trait AuthorizationOracle {
     type Principal;
     type Subject;
     type Err;
 
+    #[allow(clippy::type_complexity)]
     fn authorized(
         &self,
         principal: &Self::Principal,
         subjects: &[Self::Subject],
     ) -> Result<Authorizations<Authorization<Self::Subject>, Self::Principal>, Self::Err>;
 }
+
+/// Oracle that decides authorization for a single principal from its policy. A subject
+/// is a resource and action pair and is authorized when the policy combines to allow
+/// it in the oracle's environment.
+pub struct PolicyOracle<RMatch, AMatch, CExp, Env, Prin = ()> {
+    policy: Policy<Assertion<RMatch, AMatch, CExp>>,
+    environment: Env,
+    principal: std::marker::PhantomData<Prin>,
+}
+
+impl<RMatch, AMatch, CExp, Env, Prin> PolicyOracle<RMatch, AMatch, CExp, Env, Prin> {
+    /// Create an oracle from a principal's policy and the environment in which its
+    /// conditions are evaluated.
+    pub fn new(policy: Policy<Assertion<RMatch, AMatch, CExp>>, environment: Env) -> Self {
+        PolicyOracle {
+            policy,
+            environment,
+            principal: std::marker::PhantomData,
+        }
+    }
+
+    /// The principal's policy.
+    pub fn policy(&self) -> &Policy<Assertion<RMatch, AMatch, CExp>> {
+        &self.policy
+    }
+
+    /// The environment in which conditions are evaluated.
+    pub fn environment(&self) -> &Env {
+        &self.environment
+    }
+
+    /// Evaluate the policy for many subjects at once. Each distinct condition is evaluated
+    /// in the environment at most once across the batch and its result reused for every
+    /// subject whose assertions refer to it. Results are in subject order and equal those
+    /// of [Policy::evaluate] for each subject.
+    pub fn authorize_batch<R, A>(
+        &self,
+        _principal: &Prin,
+        subjects: &[(R, A)],
+    ) -> Vec<ComputedEffect>
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Eq + Hash,
+        Env: Environment<CExp = CExp>,
+    {
+        let mut conditions: HashMap<&CExp, bool> = HashMap::new();
+        subjects
+            .iter()
+            .map(|(resource, action)| {
+                self.policy
+                    .iter()
+                    .filter(|assertion| assertion.applies_to_subject(resource, action))
+                    .map(|assertion| match assertion {
+                        Assertion::Unconditional(_, _, eff) => eff.into(),
+                        Assertion::Conditional(_, _, eff, condition) => {
+                            let holds = *conditions
+                                .entry(condition)
+                                .or_insert_with(|| self.environment.evaluate(condition));
+                            if holds {
+                                eff.into()
+                            } else {
+                                SILENT
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl<R, A, RMatch, AMatch, CExp, Env, Prin> AuthorizationOracle
+    for PolicyOracle<RMatch, AMatch, CExp, Env, Prin>
+where
+    R: Clone,
+    A: Clone,
+    RMatch: Matcher<Target = R>,
+    AMatch: Matcher<Target = A>,
+    CExp: Clone,
+    Env: Environment<CExp = CExp>,
+    Prin: Clone,
+{
+    type Principal = Prin;
+    type Subject = (R, A);
+    type Err = std::convert::Infallible;
+
+    fn authorized(
+        &self,
+        principal: &Self::Principal,
+        subjects: &[Self::Subject],
+    ) -> Result<Authorizations<Authorization<Self::Subject>, Self::Principal>, Self::Err> {
+        let items = subjects.iter().map(|(resource, action)| {
+            let authorized = self
+                .policy
+                .evaluate(resource, action, &self.environment)
+                .authorized();
+            Authorization((resource.clone(), action.clone()), authorized)
+        });
+        Ok(Authorizations::new(principal.clone(), items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::effect::{Effect, ALLOW};
+    use crate::environment::TrivialEnv;
+    use crate::matcher::{EqualityMatcher, ExtendedMatcher};
+
+    use super::*;
+
+    type StrMatcher = EqualityMatcher<&'static str>;
+    type TestOracle = PolicyOracle<StrMatcher, StrMatcher, bool, TrivialEnv, &'static str>;
+
+    fn oracle() -> TestOracle {
+        let policy = vec![
+            Assertion::Unconditional("r".into(), StrMatcher::match_any(), Effect::ALLOW),
+            Assertion::Conditional("r".into(), "write".into(), Effect::DENY, true),
+            Assertion::Conditional("r2".into(), "read".into(), Effect::ALLOW, false),
+        ];
+        PolicyOracle::new(policy.into(), TrivialEnv)
+    }
+
+    #[test]
+    fn test_authorizations_builder() {
+        let mut builder = AuthorizationsBuilder::new("p");
+        builder.push("s1", true).push("s2", true);
+        let all = builder.build();
+
+        assert_eq!(*all.principal(), "p");
+        assert_eq!(all.as_slice().len(), 2);
+        assert!(all.authorized());
+
+        let mut builder = AuthorizationsBuilder::new("p");
+        builder.push("s1", true).push("s2", false).push("s3", true);
+        let some = builder.build();
+
+        assert_eq!(
+            some.as_slice()
+                .iter()
+                .map(|azn| (*azn.subject(), azn.authorized()))
+                .collect::<Vec<_>>(),
+            vec![("s1", true), ("s2", false), ("s3", true)]
+        );
+        assert!(!some.authorized());
+
+        let none = AuthorizationsBuilder::<&str, _>::new("p").build();
+        assert!(!none.authorized());
+    }
+
+    #[test]
+    fn test_allowed_denied() {
+        let mut builder = AuthorizationsBuilder::new("p");
+        builder
+            .push("s1", true)
+            .push("s2", false)
+            .push("s3", true)
+            .push("s4", false);
+        let mixed = builder.build();
+
+        assert_eq!(
+            mixed
+                .allowed()
+                .map(|azn| *azn.subject())
+                .collect::<Vec<_>>(),
+            vec!["s1", "s3"]
+        );
+        assert_eq!(
+            mixed.denied().map(|azn| *azn.subject()).collect::<Vec<_>>(),
+            vec!["s2", "s4"]
+        );
+
+        let none = AuthorizationsBuilder::<&str, _>::new("p").build();
+        assert_eq!(none.allowed().count(), 0);
+        assert_eq!(none.denied().count(), 0);
+    }
+
+    #[test]
+    fn test_authorize_batch() {
+        use std::cell::Cell;
+
+        struct CountingEnv(Cell<usize>);
+        impl Environment for CountingEnv {
+            type CExp = &'static str;
+
+            fn evaluate<Exp>(&self, exp: Exp) -> bool
+            where
+                Exp: std::borrow::Borrow<Self::CExp>,
+            {
+                self.0.set(self.0.get() + 1);
+                *exp.borrow() == "weekday"
+            }
+        }
+
+        let policy = vec![
+            Assertion::Conditional(
+                StrMatcher::match_any(),
+                "read".into(),
+                Effect::ALLOW,
+                "weekday",
+            ),
+            Assertion::Conditional("r".into(), StrMatcher::match_any(), Effect::DENY, "holiday"),
+            Assertion::Conditional("r2".into(), "write".into(), Effect::ALLOW, "weekday"),
+        ];
+        let oracle: PolicyOracle<_, _, _, _, &str> =
+            PolicyOracle::new(policy.into(), CountingEnv(Cell::new(0)));
+        let subjects = [
+            ("r", "read"),
+            ("r2", "read"),
+            ("r2", "write"),
+            ("r3", "read"),
+            ("r", "write"),
+        ];
+
+        let actual = oracle.authorize_batch(&"p", &subjects);
+
+        assert_eq!(oracle.environment().0.get(), 2);
+        assert_eq!(actual, vec![ALLOW, ALLOW, ALLOW, ALLOW, SILENT]);
+
+        oracle.environment().0.set(0);
+        let expected: Vec<ComputedEffect> = subjects
+            .iter()
+            .map(|(r, a)| oracle.policy().evaluate(r, a, oracle.environment()))
+            .collect();
+        assert_eq!(actual, expected);
+        // evaluated separately, conditions are evaluated once per applicable assertion
+        assert_eq!(oracle.environment().0.get(), 6);
+    }
+
+    #[test]
+    fn test_policy_oracle() {
+        let subjects = [("r", "read"), ("r", "write"), ("r2", "read")];
+
+        let actual = oracle().authorized(&"p", &subjects).unwrap();
+
+        assert_eq!(*actual.principal(), "p");
+        assert_eq!(
+            actual
+                .as_slice()
+                .iter()
+                .map(|azn| (*azn.subject(), azn.authorized()))
+                .collect::<Vec<_>>(),
+            vec![
+                (("r", "read"), true),
+                (("r", "write"), false),
+                (("r2", "read"), false),
+            ]
+        );
+        assert!(!actual.authorized());
+    }
+
+    #[test]
+    fn test_policy_oracle_all_authorized() {
+        let actual = oracle()
+            .authorized(&"p", &[("r", "read"), ("r", "list")])
+            .unwrap();
+
+        assert!(actual.authorized());
+    }
+
+    #[test]
+    fn test_policy_oracle_no_subjects() {
+        let actual = oracle().authorized(&"p", &[]).unwrap();
+
+        assert!(actual.as_slice().is_empty());
+        assert!(!actual.authorized());
+    }
+}
@@ -3,6 +3,8 @@
 //!
 
 use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
 
 /// Contextual computations. An environment is considered unreliable generally
 /// so its methods return a `Result` for error signaling.
@@ -25,6 +27,58 @@ pub trait FallibleEnvironment {
         Exp: Borrow<Self::CExp>;
 }
 
+/// Use a fallible environment where an infallible one is expected. Expressions that fail
+/// to evaluate are taken to be `on_error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fallible<E> {
+    inner: E,
+    on_error: bool,
+}
+
+impl<E> Fallible<E> {
+    /// Wrap an environment, treating failed expressions as `on_error`.
+    pub fn new(inner: E, on_error: bool) -> Self {
+        Fallible { inner, on_error }
+    }
+
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+impl<E> Environment for Fallible<E>
+where
+    E: FallibleEnvironment,
+{
+    type CExp = E::CExp;
+
+    fn evaluate<Exp>(&self, exp: Exp) -> bool
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        self.inner.fallible_evaluate(exp).unwrap_or(self.on_error)
+    }
+}
+
+/// Use an infallible environment where a fallible one is expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Infallible<E>(pub E);
+
+impl<E> FallibleEnvironment for Infallible<E>
+where
+    E: Environment,
+{
+    type CExp = E::CExp;
+    type Err = std::convert::Infallible;
+
+    fn fallible_evaluate<Exp>(&self, exp: Exp) -> Result<bool, Self::Err>
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        Ok(self.0.evaluate(exp))
+    }
+}
+
 /// Enironment for which expressions always evaluate true.
 #[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
 pub struct PositiveEnvironment<CExp = ()>(std::marker::PhantomData<CExp>);
@@ -86,6 +140,209 @@ impl FallibleEnvironment for TrivialEnv {
     }
 }
 
+/// Environment that forces selected conditions to fixed values and delegates
+/// everything else to an inner environment. Useful in tests.
+#[derive(Debug, Clone)]
+pub struct OverrideEnvironment<E>
+where
+    E: Environment,
+{
+    inner: E,
+    overrides: HashMap<E::CExp, bool>,
+}
+
+impl<E> OverrideEnvironment<E>
+where
+    E: Environment,
+    E::CExp: Eq + Hash,
+{
+    /// Create an environment with no overrides.
+    pub fn new(inner: E) -> Self {
+        OverrideEnvironment {
+            inner,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Force a condition to evaluate to `value`.
+    pub fn with(mut self, exp: E::CExp, value: bool) -> Self {
+        self.overrides.insert(exp, value);
+        self
+    }
+}
+
+impl<E> Environment for OverrideEnvironment<E>
+where
+    E: Environment,
+    E::CExp: Eq + Hash,
+{
+    type CExp = E::CExp;
+
+    fn evaluate<Exp>(&self, exp: Exp) -> bool
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        match self.overrides.get(exp.borrow()) {
+            Some(value) => *value,
+            None => self.inner.evaluate(exp),
+        }
+    }
+}
+
+/// Condition on request attributes. See [AttributeEnvironment].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AttributeExp {
+    /// The attribute is present with the value.
+    Equals(String, String),
+    /// The attribute is present with any value.
+    Present(String),
+    /// The attribute is present with one of the values.
+    OneOf(String, Vec<String>),
+}
+
+/// Environment of named string attributes, e.g. from a request context, for attribute-based
+/// conditions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AttributeEnvironment(HashMap<String, String>);
+
+impl AttributeEnvironment {
+    /// Create an environment from attributes.
+    pub fn new(attributes: HashMap<String, String>) -> Self {
+        AttributeEnvironment(attributes)
+    }
+
+    /// Look up an attribute value.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for AttributeEnvironment
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(attributes: T) -> Self {
+        AttributeEnvironment(
+            attributes
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+impl Environment for AttributeEnvironment {
+    type CExp = AttributeExp;
+
+    fn evaluate<Exp>(&self, exp: Exp) -> bool
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        match exp.borrow() {
+            AttributeExp::Equals(key, value) => self.get(key) == Some(value.as_str()),
+            AttributeExp::Present(key) => self.0.contains_key(key),
+            AttributeExp::OneOf(key, values) => match self.get(key) {
+                Some(actual) => values.iter().any(|value| value == actual),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Condition on the current time, in seconds since the Unix epoch. Windows are half-open:
+/// a start bound is inclusive and an end bound is exclusive, so consecutive windows do not
+/// overlap and an expiry takes effect at its exact instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeExp {
+    /// Holds strictly before the time.
+    Before(u64),
+    /// Holds at and after the time.
+    After(u64),
+    /// Holds at and after the start and strictly before the end.
+    Between(u64, u64),
+}
+
+/// Environment that evaluates time conditions against a fixed "now".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeEnvironment {
+    now: u64,
+}
+
+impl TimeEnvironment {
+    /// Create an environment at a time in seconds since the Unix epoch.
+    pub fn at(now: u64) -> Self {
+        TimeEnvironment { now }
+    }
+
+    /// Create an environment at the current system time.
+    pub fn now() -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        TimeEnvironment::at(now)
+    }
+
+    pub fn time(&self) -> u64 {
+        self.now
+    }
+}
+
+impl Environment for TimeEnvironment {
+    type CExp = TimeExp;
+
+    fn evaluate<Exp>(&self, exp: Exp) -> bool
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        match *exp.borrow() {
+            TimeExp::Before(end) => self.now < end,
+            TimeExp::After(start) => start <= self.now,
+            TimeExp::Between(start, end) => start <= self.now && self.now < end,
+        }
+    }
+}
+
+/// Expression for one of two condition languages. See [SplitEnvironment].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// Environment for a condition language combined from two others. Left expressions are
+/// evaluated in the left environment and right expressions in the right one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SplitEnvironment<L, R> {
+    pub left: L,
+    pub right: R,
+}
+
+impl<L, R> SplitEnvironment<L, R> {
+    pub fn new(left: L, right: R) -> Self {
+        SplitEnvironment { left, right }
+    }
+}
+
+impl<L, R> Environment for SplitEnvironment<L, R>
+where
+    L: Environment,
+    R: Environment,
+{
+    type CExp = Either<L::CExp, R::CExp>;
+
+    fn evaluate<Exp>(&self, exp: Exp) -> bool
+    where
+        Exp: Borrow<Self::CExp>,
+    {
+        match exp.borrow() {
+            Either::Left(exp) => self.left.evaluate(exp),
+            Either::Right(exp) => self.right.evaluate(exp),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +358,117 @@ mod tests {
         let env = NegativeEnvironment::default();
         assert!(!env.evaluate(()));
     }
+
+    #[test]
+    pub fn test_override_environment() {
+        let env =
+            OverrideEnvironment::new(PositiveEnvironment::<&str>::default()).with("closed", false);
+
+        assert!(!env.evaluate("closed"));
+        assert!(env.evaluate("open"));
+        assert!(env.evaluate("anything"));
+    }
+
+    fn attributes() -> AttributeEnvironment {
+        [("region", "us-east"), ("tier", "gold")]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    pub fn test_attribute_environment_present() {
+        let env = attributes();
+
+        assert!(env.evaluate(AttributeExp::Present("region".into())));
+        assert!(!env.evaluate(AttributeExp::Present("team".into())));
+    }
+
+    #[test]
+    pub fn test_attribute_environment_equals() {
+        let env = attributes();
+
+        assert!(env.evaluate(AttributeExp::Equals("region".into(), "us-east".into())));
+        assert!(!env.evaluate(AttributeExp::Equals("region".into(), "eu-west".into())));
+        assert!(!env.evaluate(AttributeExp::Equals("team".into(), "us-east".into())));
+    }
+
+    #[test]
+    pub fn test_attribute_environment_one_of() {
+        let env = attributes();
+        let tiers = vec!["silver".to_string(), "gold".to_string()];
+
+        assert!(env.evaluate(AttributeExp::OneOf("tier".into(), tiers.clone())));
+        assert!(!env.evaluate(AttributeExp::OneOf("region".into(), tiers.clone())));
+        assert!(!env.evaluate(AttributeExp::OneOf("team".into(), tiers)));
+        assert!(!env.evaluate(AttributeExp::OneOf("tier".into(), vec![])));
+    }
+
+    #[test]
+    pub fn test_time_environment_before_after() {
+        assert!(TimeEnvironment::at(99).evaluate(TimeExp::Before(100)));
+        assert!(!TimeEnvironment::at(100).evaluate(TimeExp::Before(100)));
+        assert!(!TimeEnvironment::at(99).evaluate(TimeExp::After(100)));
+        assert!(TimeEnvironment::at(100).evaluate(TimeExp::After(100)));
+        assert!(TimeEnvironment::at(101).evaluate(TimeExp::After(100)));
+    }
+
+    #[test]
+    pub fn test_time_environment_between() {
+        let window = TimeExp::Between(100, 200);
+
+        assert!(!TimeEnvironment::at(99).evaluate(window));
+        assert!(TimeEnvironment::at(100).evaluate(window));
+        assert!(TimeEnvironment::at(150).evaluate(window));
+        assert!(!TimeEnvironment::at(200).evaluate(window));
+        assert!(!TimeEnvironment::at(250).evaluate(window));
+    }
+
+    #[test]
+    pub fn test_split_environment() {
+        let env = SplitEnvironment::new(TimeEnvironment::at(150), attributes());
+
+        assert!(env.evaluate(Either::Left(TimeExp::Between(100, 200))));
+        assert!(!env.evaluate(Either::Left(TimeExp::Before(100))));
+        assert!(env.evaluate(Either::Right(AttributeExp::Equals(
+            "tier".into(),
+            "gold".into()
+        ))));
+        assert!(!env.evaluate(Either::Right(AttributeExp::Present("team".into()))));
+    }
+
+    struct FlakyEnvironment;
+
+    impl FallibleEnvironment for FlakyEnvironment {
+        type CExp = Option<bool>;
+        type Err = &'static str;
+
+        fn fallible_evaluate<Exp>(&self, exp: Exp) -> Result<bool, Self::Err>
+        where
+            Exp: Borrow<Self::CExp>,
+        {
+            exp.borrow().ok_or("unavailable")
+        }
+    }
+
+    #[test]
+    pub fn test_fallible() {
+        let closed = Fallible::new(FlakyEnvironment, false);
+        let negative = NegativeEnvironment::<Option<bool>>::default();
+
+        assert!(closed.evaluate(Some(true)));
+        assert!(!closed.evaluate(Some(false)));
+        assert_eq!(closed.evaluate(None), negative.evaluate(None));
+
+        let open = Fallible::new(FlakyEnvironment, true);
+        assert!(!open.evaluate(Some(false)));
+        assert!(open.evaluate(None));
+    }
+
+    #[test]
+    pub fn test_infallible() {
+        let env = Infallible(TrivialEnv);
+
+        assert_eq!(env.fallible_evaluate(true), Ok(true));
+        assert_eq!(env.fallible_evaluate(false), Ok(false));
+    }
 }
@@ -1,3 +1,4 @@
+pub mod condition;
 pub mod effect;
 pub mod environment;
 pub mod matcher;
@@ -5,6 +5,12 @@
 //! think it's an equivalance class but maybe something
 //! along those lines.
 
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
 /// Basic matcher trait. Represents a class of values
 /// for which inclusion can be tested.
 pub trait Matcher {
@@ -13,6 +19,16 @@ pub trait Matcher {
 
     /// Determine if a concrete target matches
     fn test(&self, target: &Self::Target) -> bool;
+
+    /// Adapt the matcher to a different target type by projecting the value to
+    /// match from the new target.
+    fn contramap<T, F>(self, project: F) -> MappedMatcher<Self, F, T>
+    where
+        Self: Sized,
+        F: Fn(&T) -> &Self::Target,
+    {
+        MappedMatcher::new(self, project)
+    }
 }
 
 /// Convenience methods for matchers. Non-trivial matchers should implement
@@ -28,7 +44,8 @@ pub trait ExtendedMatcher: Matcher {
     fn match_none() -> Self;
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Wrapper for direct equality matching. Use this to convert anything
 /// that implements `Eq` into an extended matcher.
 pub enum EqualityMatcher<T> {
@@ -46,21 +63,47 @@ impl<T> From<T> for EqualityMatcher<T> {
     }
 }
 
-impl<T> Matcher for EqualityMatcher<T>
+impl<T> EqualityMatcher<T>
 where
     T: Eq,
 {
-    type Target = T;
+    /// Determine if some target would be matched by both matchers.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        use EqualityMatcher::*;
 
-    fn test(&self, target: &Self::Target) -> bool {
+        match (self, other) {
+            (None, _) | (_, None) => false,
+            (Any, _) | (_, Any) => true,
+            (Only(l), Only(r)) => l == r,
+        }
+    }
+
+    /// Test a borrowed form of the target, e.g. a `&str` against an `EqualityMatcher<String>`,
+    /// like `HashMap::get` accepts borrowed keys.
+    pub fn test_borrowed<Q>(&self, target: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
         match self {
-            EqualityMatcher::Only(ref t) => t == target,
+            EqualityMatcher::Only(t) => t.borrow() == target,
             EqualityMatcher::Any => true,
             EqualityMatcher::None => false,
         }
     }
 }
 
+impl<T> Matcher for EqualityMatcher<T>
+where
+    T: Eq,
+{
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        self.test_borrowed(target)
+    }
+}
+
 impl<T> ExtendedMatcher for EqualityMatcher<T>
 where
     EqualityMatcher<T>: Matcher,
@@ -79,46 +122,1473 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+/// Match enum values by variant, ignoring any payload.
+pub struct DiscriminantMatcher<T>(std::mem::Discriminant<T>);
 
-    use super::*;
+impl<T> DiscriminantMatcher<T> {
+    /// Create a matcher for the variant of an example value.
+    pub fn of(example: &T) -> Self {
+        DiscriminantMatcher(std::mem::discriminant(example))
+    }
+}
 
-    use crate::matcher::EqualityMatcher;
+impl<T> From<std::mem::Discriminant<T>> for DiscriminantMatcher<T> {
+    fn from(discriminant: std::mem::Discriminant<T>) -> Self {
+        DiscriminantMatcher(discriminant)
+    }
+}
 
-    type StrMatcher = EqualityMatcher<&'static str>;
+impl<T> Matcher for DiscriminantMatcher<T> {
+    type Target = T;
 
-    #[test]
-    fn equality_matcher_eq() {
-        let m = StrMatcher::match_only("foo");
-        assert!(m.test(&"foo"));
+    fn test(&self, target: &Self::Target) -> bool {
+        std::mem::discriminant(target) == self.0
     }
+}
 
-    #[test]
-    fn equality_matcher_neq() {
-        let m = StrMatcher::match_only("foo");
-        assert!(!m.test(&"bar"));
+#[cfg(feature = "regex")]
+/// Match strings against a regular expression. The target type is any string-like
+/// type, typically `String` or `&str`.
+#[derive(Debug, Clone)]
+pub struct RegexMatcher<T = String>(regex::Regex, std::marker::PhantomData<T>);
+
+#[cfg(feature = "regex")]
+impl<T> RegexMatcher<T> {
+    /// Compile a matcher from a pattern. The pattern is used as-is so it must be
+    /// anchored explicitly to match whole targets.
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        regex::Regex::new(pattern).map(Self::from)
     }
 
-    #[test]
-    fn equality_matcher_never() {
-        let m = StrMatcher::match_none();
-        assert!(!m.test(&"foo"));
+    /// The compiled expression.
+    pub fn as_regex(&self) -> &regex::Regex {
+        &self.0
     }
+}
 
-    #[test]
-    fn equality_matcher_always() {
-        let m = StrMatcher::match_any();
-        assert!(m.test(&"foo"));
+#[cfg(feature = "regex")]
+impl<T> From<regex::Regex> for RegexMatcher<T> {
+    fn from(regex: regex::Regex) -> Self {
+        RegexMatcher(regex, std::marker::PhantomData)
     }
+}
 
-    #[test]
-    fn equality_matcher_into() {
-        let foo = "foo";
-        let mx = StrMatcher::match_only(foo);
-        let m: StrMatcher = foo.into();
+#[cfg(feature = "regex")]
+impl<T> Matcher for RegexMatcher<T>
+where
+    T: AsRef<str>,
+{
+    type Target = T;
 
-        assert_eq!(m.test(&foo), mx.test(&foo));
-        assert_eq!(m.test(&"not foo"), mx.test(&"not foo"))
+    fn test(&self, target: &Self::Target) -> bool {
+        self.0.is_match(target.as_ref())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl<T> ExtendedMatcher for RegexMatcher<T>
+where
+    T: AsRef<str>,
+{
+    fn match_only(target: <Self as Matcher>::Target) -> Self {
+        let pattern = format!("^{}$", regex::escape(target.as_ref()));
+        Self::new(&pattern).expect("escaped pattern compiles")
+    }
+
+    fn match_any() -> Self {
+        // dot-all so that multi-line targets match too
+        Self::new("(?s)^.*$").expect("constant pattern compiles")
+    }
+
+    fn match_none() -> Self {
+        // nothing can follow the end of text
+        Self::new("$.^").expect("constant pattern compiles")
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Match JSON documents by the value found at a [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901).
+/// A document without a value at the pointer does not match.
+pub struct JsonPointerMatcher {
+    pointer: String,
+    expected: serde_json::Value,
+}
+
+#[cfg(feature = "serde")]
+impl JsonPointerMatcher {
+    /// Create a matcher for documents whose value at `pointer` equals `expected`.
+    pub fn new(pointer: impl Into<String>, expected: serde_json::Value) -> Self {
+        JsonPointerMatcher {
+            pointer: pointer.into(),
+            expected,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Matcher for JsonPointerMatcher {
+    type Target = serde_json::Value;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        target.pointer(&self.pointer) == Some(&self.expected)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match strings that start with a prefix. Useful for hierarchical names, e.g.
+/// `docs/` matches everything under `docs`. The empty prefix matches everything.
+pub enum PrefixMatcher {
+    /// Match strings that start with the prefix.
+    Prefix(String),
+    /// Match a specific string.
+    Exact(String),
+    /// Match nothing.
+    None,
+}
+
+impl PrefixMatcher {
+    /// Create a matcher for a prefix.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        PrefixMatcher::Prefix(prefix.into())
+    }
+}
+
+impl Matcher for PrefixMatcher {
+    type Target = String;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        match self {
+            PrefixMatcher::Prefix(prefix) => target.starts_with(prefix.as_str()),
+            PrefixMatcher::Exact(value) => target == value,
+            PrefixMatcher::None => false,
+        }
+    }
+}
+
+impl ExtendedMatcher for PrefixMatcher {
+    fn match_only(target: String) -> Self {
+        PrefixMatcher::Exact(target)
+    }
+
+    fn match_any() -> Self {
+        PrefixMatcher::new("")
+    }
+
+    fn match_none() -> Self {
+        PrefixMatcher::None
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match strings that end with a suffix. The empty suffix matches everything.
+pub enum SuffixMatcher {
+    /// Match strings that end with the suffix.
+    Suffix(String),
+    /// Match a specific string.
+    Exact(String),
+    /// Match nothing.
+    None,
+}
+
+impl SuffixMatcher {
+    /// Create a matcher for a suffix.
+    pub fn new(suffix: impl Into<String>) -> Self {
+        SuffixMatcher::Suffix(suffix.into())
+    }
+}
+
+impl Matcher for SuffixMatcher {
+    type Target = String;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        match self {
+            SuffixMatcher::Suffix(suffix) => target.ends_with(suffix.as_str()),
+            SuffixMatcher::Exact(value) => target == value,
+            SuffixMatcher::None => false,
+        }
+    }
+}
+
+impl ExtendedMatcher for SuffixMatcher {
+    fn match_only(target: String) -> Self {
+        SuffixMatcher::Exact(target)
+    }
+
+    fn match_any() -> Self {
+        SuffixMatcher::new("")
+    }
+
+    fn match_none() -> Self {
+        SuffixMatcher::None
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match strings that contain a substring. The empty substring matches everything.
+pub enum ContainsMatcher {
+    /// Match strings that contain the substring.
+    Contains(String),
+    /// Match a specific string.
+    Exact(String),
+    /// Match nothing.
+    None,
+}
+
+impl ContainsMatcher {
+    /// Create a matcher for a substring.
+    pub fn new(substring: impl Into<String>) -> Self {
+        ContainsMatcher::Contains(substring.into())
+    }
+}
+
+impl Matcher for ContainsMatcher {
+    type Target = String;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        match self {
+            ContainsMatcher::Contains(substring) => target.contains(substring.as_str()),
+            ContainsMatcher::Exact(value) => target == value,
+            ContainsMatcher::None => false,
+        }
+    }
+}
+
+impl ExtendedMatcher for ContainsMatcher {
+    fn match_only(target: String) -> Self {
+        ContainsMatcher::Exact(target)
+    }
+
+    fn match_any() -> Self {
+        ContainsMatcher::new("")
+    }
+
+    fn match_none() -> Self {
+        ContainsMatcher::None
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match strings against a glob pattern, e.g. `s3:Get*`. `*` matches any run of characters,
+/// including none, and `?` matches exactly one character. A backslash makes the character
+/// after it literal.
+pub struct GlobMatcher(Option<Vec<GlobToken>>);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+enum GlobToken {
+    Literal(char),
+    One,
+    Run,
+}
+
+impl GlobMatcher {
+    /// Create a matcher for a glob pattern.
+    pub fn new(pattern: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                '*' => GlobToken::Run,
+                '?' => GlobToken::One,
+                '\\' => GlobToken::Literal(chars.next().unwrap_or('\\')),
+                c => GlobToken::Literal(c),
+            });
+        }
+        GlobMatcher(Some(tokens))
+    }
+}
+
+impl Matcher for GlobMatcher {
+    type Target = String;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        let tokens = match self.0 {
+            Some(ref tokens) => tokens,
+            None => return false,
+        };
+        let text: Vec<char> = target.chars().collect();
+
+        // position of the last run and of the text it was tried against, for backtracking
+        let mut backtrack: Option<(usize, usize)> = None;
+        let (mut t, mut s) = (0, 0);
+        while s < text.len() {
+            match tokens.get(t) {
+                Some(GlobToken::Run) => {
+                    backtrack = Some((t, s));
+                    t += 1;
+                }
+                Some(GlobToken::One) => {
+                    t += 1;
+                    s += 1;
+                }
+                Some(GlobToken::Literal(c)) if *c == text[s] => {
+                    t += 1;
+                    s += 1;
+                }
+                _ => match backtrack {
+                    // let the run absorb one more character
+                    Some((run, start)) => {
+                        backtrack = Some((run, start + 1));
+                        t = run + 1;
+                        s = start + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        tokens[t..].iter().all(|token| *token == GlobToken::Run)
+    }
+}
+
+impl ExtendedMatcher for GlobMatcher {
+    fn match_only(target: Self::Target) -> Self {
+        GlobMatcher(Some(target.chars().map(GlobToken::Literal).collect()))
+    }
+
+    fn match_any() -> Self {
+        GlobMatcher::new("*")
+    }
+
+    fn match_none() -> Self {
+        GlobMatcher(None)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+/// Match ordered values that fall within a range.
+pub struct RangeMatcher<T> {
+    /// Lower bound of matched values.
+    pub start: Bound<T>,
+    /// Upper bound of matched values.
+    pub end: Bound<T>,
+}
+
+impl<T> RangeMatcher<T> {
+    /// Create a matcher for values within the bounds.
+    pub fn new(start: Bound<T>, end: Bound<T>) -> Self {
+        RangeMatcher { start, end }
+    }
+
+    /// Match values greater than or equal to `start`.
+    pub fn at_least(start: T) -> Self {
+        RangeMatcher::new(Bound::Included(start), Bound::Unbounded)
+    }
+
+    /// Match values less than or equal to `end`.
+    pub fn at_most(end: T) -> Self {
+        RangeMatcher::new(Bound::Unbounded, Bound::Included(end))
+    }
+
+    /// Match values between `start` and `end`, inclusive.
+    pub fn between(start: T, end: T) -> Self {
+        RangeMatcher::new(Bound::Included(start), Bound::Included(end))
+    }
+}
+
+impl<T> Matcher for RangeMatcher<T>
+where
+    T: Ord,
+{
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        (self.start.as_ref(), self.end.as_ref()).contains(target)
+    }
+}
+
+impl<T> ExtendedMatcher for RangeMatcher<T>
+where
+    T: Ord + Clone + Default,
+{
+    fn match_only(target: T) -> Self {
+        RangeMatcher::between(target.clone(), target)
+    }
+
+    fn match_any() -> Self {
+        RangeMatcher::new(Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// The empty range `(d, d)` where `d` is the default value.
+    fn match_none() -> Self {
+        RangeMatcher::new(Bound::Excluded(T::default()), Bound::Excluded(T::default()))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Match any value in a set. Prefer this to aggregating many equality matchers.
+pub enum OneOfMatcher<T> {
+    /// Match values in the set. An empty set matches nothing.
+    OneOf(HashSet<T>),
+    /// Match any value.
+    Any,
+}
+
+impl<T> PartialEq for OneOfMatcher<T>
+where
+    T: Eq + Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (OneOfMatcher::OneOf(l), OneOfMatcher::OneOf(r)) => l == r,
+            (OneOfMatcher::Any, OneOfMatcher::Any) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T> Eq for OneOfMatcher<T> where T: Eq + Hash {}
+
+impl<T> FromIterator<T> for OneOfMatcher<T>
+where
+    T: Eq + Hash,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        OneOfMatcher::OneOf(items.into_iter().collect())
+    }
+}
+
+impl<T> OneOfMatcher<T>
+where
+    T: Eq + Hash,
+{
+    /// Match any of a finite set of targets by exact membership.
+    pub fn match_all_of<I>(targets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        targets.into_iter().collect()
+    }
+}
+
+impl<T> From<HashSet<T>> for OneOfMatcher<T> {
+    fn from(items: HashSet<T>) -> Self {
+        OneOfMatcher::OneOf(items)
+    }
+}
+
+impl<T> Matcher for OneOfMatcher<T>
+where
+    T: Eq + Hash,
+{
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        match self {
+            OneOfMatcher::OneOf(items) => items.contains(target),
+            OneOfMatcher::Any => true,
+        }
+    }
+}
+
+impl<T> ExtendedMatcher for OneOfMatcher<T>
+where
+    T: Eq + Hash,
+{
+    fn match_only(target: T) -> Self {
+        [target].into_iter().collect()
+    }
+
+    fn match_any() -> Self {
+        OneOfMatcher::Any
+    }
+
+    fn match_none() -> Self {
+        OneOfMatcher::OneOf(HashSet::new())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match ordered values that fall within any of a set of inclusive ranges. Overlapping
+/// ranges are merged at construction so membership is a binary search over disjoint,
+/// sorted intervals.
+pub struct IntervalSetMatcher<T>(Vec<(T, T)>);
+
+impl<T: Ord> IntervalSetMatcher<T> {
+    /// Create a matcher from inclusive ranges. Empty ranges are ignored.
+    pub fn new<I: IntoIterator<Item = RangeInclusive<T>>>(ranges: I) -> Self {
+        let mut ranges: Vec<(T, T)> = ranges
+            .into_iter()
+            .map(RangeInclusive::into_inner)
+            .filter(|(start, end)| start <= end)
+            .collect();
+        ranges.sort_by(|l, r| l.0.cmp(&r.0));
+
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => {
+                    if end > last.1 {
+                        last.1 = end;
+                    }
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+        IntervalSetMatcher(merged)
+    }
+
+    /// The disjoint intervals, in order.
+    pub fn intervals(&self) -> &[(T, T)] {
+        &self.0
+    }
+}
+
+impl<T: Ord> FromIterator<RangeInclusive<T>> for IntervalSetMatcher<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(ranges: I) -> Self {
+        IntervalSetMatcher::new(ranges)
+    }
+}
+
+impl<T: Ord> Matcher for IntervalSetMatcher<T> {
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        let after = self.0.partition_point(|(start, _)| start <= target);
+        after > 0 && *target <= self.0[after - 1].1
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+/// Match values the inner matcher does not.
+pub struct NotMatcher<M>(pub M);
+
+impl<M> Matcher for NotMatcher<M>
+where
+    M: Matcher,
+{
+    type Target = M::Target;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        !self.0.test(target)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match values that every inner matcher matches. Matches everything when empty.
+pub struct AllOfMatcher<M>(pub Vec<M>);
+
+impl<M> FromIterator<M> for AllOfMatcher<M> {
+    fn from_iter<I: IntoIterator<Item = M>>(items: I) -> Self {
+        AllOfMatcher(items.into_iter().collect())
+    }
+}
+
+impl<M> Matcher for AllOfMatcher<M>
+where
+    M: Matcher,
+{
+    type Target = M::Target;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        self.0.iter().all(|m| m.test(target))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+/// Match values that some inner matcher matches. Matches nothing when empty.
+pub struct AnyOfMatcher<M>(pub Vec<M>);
+
+impl<M> FromIterator<M> for AnyOfMatcher<M> {
+    fn from_iter<I: IntoIterator<Item = M>>(items: I) -> Self {
+        AnyOfMatcher(items.into_iter().collect())
+    }
+}
+
+impl<M> Matcher for AnyOfMatcher<M>
+where
+    M: Matcher,
+{
+    type Target = M::Target;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        self.0.iter().any(|m| m.test(target))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+/// Match pairs by matching each element, e.g. a resource and action together.
+pub struct PairMatcher<M1, M2>(pub M1, pub M2);
+
+impl<M1, M2> PairMatcher<M1, M2> {
+    pub fn new(first: M1, second: M2) -> Self {
+        PairMatcher(first, second)
+    }
+}
+
+impl<M1, M2> Matcher for PairMatcher<M1, M2>
+where
+    M1: Matcher,
+    M2: Matcher,
+{
+    type Target = (M1::Target, M2::Target);
+
+    fn test(&self, (first, second): &Self::Target) -> bool {
+        self.0.test(first) && self.1.test(second)
+    }
+}
+
+/// Match a target by matching a value projected from it, e.g. a field of a struct.
+/// See [Matcher::contramap].
+pub struct MappedMatcher<M, F, T> {
+    inner: M,
+    project: F,
+    target: std::marker::PhantomData<fn(&T)>,
+}
+
+impl<M, F, T> MappedMatcher<M, F, T> {
+    /// Create a matcher that applies `inner` to the projection of each target.
+    pub fn new(inner: M, project: F) -> Self {
+        MappedMatcher {
+            inner,
+            project,
+            target: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<M, F, T> Matcher for MappedMatcher<M, F, T>
+where
+    M: Matcher,
+    F: Fn(&T) -> &M::Target,
+{
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        self.inner.test((self.project)(target))
+    }
+}
+
+/// Probabilistic membership matcher for very large sets, e.g. allowlists of millions of
+/// ids. A match means the target is *maybe* a member: it is wrong at roughly the configured
+/// false-positive rate, so follow a match with an authoritative check. A non-match is
+/// definitive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BloomMatcher<T> {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+    false_positive_rate: f64,
+    target: std::marker::PhantomData<fn(&T)>,
+}
+
+impl<T: Hash> BloomMatcher<T> {
+    /// Create an empty matcher sized for `capacity` members at the given false-positive
+    /// rate, which must be strictly between 0 and 1.
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false positive rate must be strictly between 0 and 1"
+        );
+        let ln2 = std::f64::consts::LN_2;
+        let capacity = capacity.max(1) as f64;
+        let num_bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2)).ceil() as u64;
+        let num_bits = num_bits.max(64);
+        let num_hashes = ((num_bits as f64 / capacity) * ln2).round().max(1.0) as u32;
+        BloomMatcher {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+            false_positive_rate,
+            target: std::marker::PhantomData,
+        }
+    }
+
+    /// Create a matcher for the members at the given false-positive rate.
+    pub fn with_members<I: IntoIterator<Item = T>>(members: I, false_positive_rate: f64) -> Self {
+        let members: Vec<T> = members.into_iter().collect();
+        let mut matcher = BloomMatcher::new(members.len(), false_positive_rate);
+        for member in &members {
+            matcher.insert(member);
+        }
+        matcher
+    }
+
+    /// Add a member.
+    pub fn insert(&mut self, member: &T) {
+        for bit in self.positions(member) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// The false-positive rate the matcher was sized for.
+    pub fn false_positive_rate(&self) -> f64 {
+        self.false_positive_rate
+    }
+
+    fn positions(&self, member: &T) -> impl Iterator<Item = u64> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        let hash = |seed: u8| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            member.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (h1, h2) = (hash(0), hash(1) | 1);
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+impl<T: Hash> Matcher for BloomMatcher<T> {
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        self.positions(target)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+/// Object-safe form of [Matcher] for storing matchers of different types together, e.g.
+/// `Vec<Box<dyn DynMatcher<String>>>`. Every matcher is a `DynMatcher` of its target and a
+/// boxed `DynMatcher` is a matcher again. The method has its own name so that calls to
+/// [Matcher::test] stay unambiguous.
+pub trait DynMatcher<T> {
+    /// Determine if a concrete target matches
+    fn test_dyn(&self, target: &T) -> bool;
+}
+
+impl<M, T> DynMatcher<T> for M
+where
+    M: Matcher<Target = T>,
+{
+    fn test_dyn(&self, target: &T) -> bool {
+        self.test(target)
+    }
+}
+
+impl<'a, T> Matcher for Box<dyn DynMatcher<T> + 'a> {
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        self.as_ref().test_dyn(target)
+    }
+}
+
+/// Match with a predicate closure. `Matcher` cannot be implemented for bare closures: in
+/// `impl<T, F: Fn(&T) -> bool> Matcher for F` the target `T` appears only in the closure
+/// bound, which leaves it unconstrained (E0207), since one closure type could implement
+/// `Fn(&T)` for many `T`. The wrapper fixes the target type.
+pub struct FnMatcher<T, F> {
+    predicate: F,
+    target: std::marker::PhantomData<fn(&T)>,
+}
+
+impl<T, F> FnMatcher<T, F>
+where
+    F: Fn(&T) -> bool,
+{
+    /// Create a matcher that matches targets for which the predicate is true.
+    pub fn new(predicate: F) -> Self {
+        FnMatcher {
+            predicate,
+            target: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Create a matcher from a predicate closure, e.g. `matcher(|s: &String| s.len() < 10)`.
+pub fn matcher<T, F>(predicate: F) -> FnMatcher<T, F>
+where
+    F: Fn(&T) -> bool,
+{
+    FnMatcher::new(predicate)
+}
+
+impl<T, F> Matcher for FnMatcher<T, F>
+where
+    F: Fn(&T) -> bool,
+{
+    type Target = T;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        (self.predicate)(target)
+    }
+}
+
+/// Remember the results of an expensive inner matcher so that each distinct target is
+/// tested at most once.
+pub struct MemoMatcher<M>
+where
+    M: Matcher,
+{
+    inner: M,
+    results: RefCell<HashMap<M::Target, bool>>,
+}
+
+impl<M> MemoMatcher<M>
+where
+    M: Matcher,
+{
+    /// Wrap a matcher.
+    pub fn new(inner: M) -> Self {
+        MemoMatcher {
+            inner,
+            results: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Unwrap the inner matcher, discarding remembered results.
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M> Matcher for MemoMatcher<M>
+where
+    M: Matcher,
+    M::Target: Eq + Hash + Clone,
+{
+    type Target = M::Target;
+
+    fn test(&self, target: &Self::Target) -> bool {
+        if let Some(result) = self.results.borrow().get(target) {
+            return *result;
+        }
+        let result = self.inner.test(target);
+        self.results.borrow_mut().insert(target.clone(), result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use crate::matcher::EqualityMatcher;
+
+    type StrMatcher = EqualityMatcher<&'static str>;
+
+    #[test]
+    fn equality_matcher_eq() {
+        let m = StrMatcher::match_only("foo");
+        assert!(m.test(&"foo"));
+    }
+
+    #[test]
+    fn equality_matcher_neq() {
+        let m = StrMatcher::match_only("foo");
+        assert!(!m.test(&"bar"));
+    }
+
+    #[test]
+    fn equality_matcher_never() {
+        let m = StrMatcher::match_none();
+        assert!(!m.test(&"foo"));
+    }
+
+    #[test]
+    fn equality_matcher_always() {
+        let m = StrMatcher::match_any();
+        assert!(m.test(&"foo"));
+    }
+
+    #[test]
+    fn equality_matcher_into() {
+        let foo = "foo";
+        let mx = StrMatcher::match_only(foo);
+        let m: StrMatcher = foo.into();
+
+        assert_eq!(m.test(&foo), mx.test(&foo));
+        assert_eq!(m.test(&"not foo"), mx.test(&"not foo"))
+    }
+
+    #[test]
+    fn equality_matcher_borrowed() {
+        let m = EqualityMatcher::from(String::from("foo"));
+        let target: &str = "foo";
+
+        assert!(m.test_borrowed(target));
+        assert!(!m.test_borrowed("bar"));
+        assert!(EqualityMatcher::<String>::match_any().test_borrowed("bar"));
+        assert!(!EqualityMatcher::<String>::match_none().test_borrowed("foo"));
+    }
+
+    #[test]
+    fn one_of_matcher_all_of() {
+        let m = OneOfMatcher::match_all_of(["a", "b", "c"]);
+
+        assert!(m.test(&"a"));
+        assert!(m.test(&"b"));
+        assert!(m.test(&"c"));
+        assert!(!m.test(&"d"));
+        assert!(!m.test(&""));
+        assert!(!OneOfMatcher::<&str>::match_all_of([]).test(&"a"));
+    }
+
+    #[test]
+    fn equality_matcher_overlaps() {
+        let foo = StrMatcher::match_only("foo");
+        let bar = StrMatcher::match_only("bar");
+        let any = StrMatcher::match_any();
+        let none = StrMatcher::match_none();
+
+        assert!(foo.overlaps(&foo));
+        assert!(!foo.overlaps(&bar));
+        assert!(foo.overlaps(&any));
+        assert!(!foo.overlaps(&none));
+
+        assert!(any.overlaps(&foo));
+        assert!(any.overlaps(&any));
+        assert!(!any.overlaps(&none));
+
+        assert!(!none.overlaps(&foo));
+        assert!(!none.overlaps(&any));
+        assert!(!none.overlaps(&none));
+    }
+
+    #[allow(dead_code)]
+    enum Resource {
+        Document(&'static str),
+        Folder(&'static str),
+    }
+
+    #[test]
+    fn discriminant_matcher_ignores_payload() {
+        let m = DiscriminantMatcher::of(&Resource::Document("a"));
+
+        assert!(m.test(&Resource::Document("a")));
+        assert!(m.test(&Resource::Document("b")));
+        assert!(!m.test(&Resource::Folder("a")));
+    }
+
+    #[test]
+    fn discriminant_matcher_from() {
+        let m: DiscriminantMatcher<_> = std::mem::discriminant(&Resource::Folder("x")).into();
+
+        assert!(m.test(&Resource::Folder("y")));
+        assert!(!m.test(&Resource::Document("x")));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matcher_anchored() {
+        let m = RegexMatcher::<String>::new("^doc-[0-9]+$").unwrap();
+
+        assert!(m.test(&"doc-42".to_string()));
+        assert!(!m.test(&"doc-42x".to_string()));
+        assert!(!m.test(&"my-doc-42".to_string()));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matcher_unanchored() {
+        let m = RegexMatcher::<&str>::new("doc").unwrap();
+
+        assert!(m.test(&"my-doc-42"));
+        assert!(!m.test(&"image"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matcher_invalid() {
+        assert!(RegexMatcher::<String>::new("(unclosed").is_err());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matcher_only() {
+        let m = RegexMatcher::<&str>::match_only("a.b");
+
+        assert!(m.test(&"a.b"));
+        assert!(!m.test(&"axb"));
+        assert!(!m.test(&"a.bc"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matcher_any() {
+        let m = RegexMatcher::<&str>::match_any();
+
+        assert!(m.test(&""));
+        assert!(m.test(&"foo"));
+        assert!(m.test(&"foo\nbar"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_matcher_none() {
+        let m = RegexMatcher::<&str>::match_none();
+
+        assert!(!m.test(&""));
+        assert!(!m.test(&"foo"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_pointer_matcher_present() {
+        let m = JsonPointerMatcher::new("/metadata/tenant", serde_json::json!("acme"));
+
+        assert!(m.test(&serde_json::json!({"metadata": {"tenant": "acme"}})));
+        assert!(!m.test(&serde_json::json!({"metadata": {"tenant": "other"}})));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_pointer_matcher_missing() {
+        let m = JsonPointerMatcher::new("/metadata/tenant", serde_json::json!("acme"));
+
+        assert!(!m.test(&serde_json::json!({"metadata": {}})));
+        assert!(!m.test(&serde_json::json!("acme")));
+    }
+
+    #[test]
+    fn prefix_matcher() {
+        let m = PrefixMatcher::new("docs/");
+        assert_eq!(m, PrefixMatcher::Prefix("docs/".to_string()));
+
+        assert!(m.test(&"docs/reports".to_string()));
+        assert!(m.test(&"docs/".to_string()));
+        assert!(!m.test(&"images/docs/".to_string()));
+        assert!(!m.test(&"docs".to_string()));
+    }
+
+    #[test]
+    fn suffix_matcher() {
+        let m = SuffixMatcher::new(".pdf");
+
+        assert!(m.test(&"docs/report.pdf".to_string()));
+        assert!(!m.test(&"docs/report.pdf.bak".to_string()));
+    }
+
+    #[test]
+    fn contains_matcher() {
+        let m = ContainsMatcher::new("reports");
+
+        assert!(m.test(&"docs/reports/2024".to_string()));
+        assert!(!m.test(&"docs/report/2024".to_string()));
+    }
+
+    #[test]
+    fn string_matchers_match_only_is_exact() {
+        let docs = "docs".to_string();
+
+        assert!(PrefixMatcher::match_only(docs.clone()).test(&docs));
+        assert!(!PrefixMatcher::match_only(docs.clone()).test(&"docs/x".to_string()));
+        assert!(!PrefixMatcher::match_only(docs.clone()).test(&"docs-secret".to_string()));
+
+        assert!(SuffixMatcher::match_only(docs.clone()).test(&docs));
+        assert!(!SuffixMatcher::match_only(docs.clone()).test(&"my-docs".to_string()));
+
+        assert!(ContainsMatcher::match_only(docs.clone()).test(&docs));
+        assert!(!ContainsMatcher::match_only("a".to_string()).test(&"banana".to_string()));
+
+        // the pattern constructors keep their semantics
+        assert!(PrefixMatcher::new("docs").test(&"docs/x".to_string()));
+    }
+
+    #[test]
+    fn string_matchers_any_and_none() {
+        let target = "docs/reports".to_string();
+
+        assert!(PrefixMatcher::match_any().test(&target));
+        assert!(SuffixMatcher::match_any().test(&target));
+        assert!(ContainsMatcher::match_any().test(&target));
+        assert!(PrefixMatcher::match_any().test(&String::new()));
+
+        assert!(!PrefixMatcher::match_none().test(&target));
+        assert!(!SuffixMatcher::match_none().test(&target));
+        assert!(!ContainsMatcher::match_none().test(&target));
+        assert!(!PrefixMatcher::match_none().test(&String::new()));
+    }
+
+    #[test]
+    fn glob_matcher_run() {
+        let m = GlobMatcher::new("s3:Get*");
+
+        assert!(m.test(&"s3:GetObject".to_string()));
+        assert!(m.test(&"s3:Get".to_string()));
+        assert!(!m.test(&"s3:PutObject".to_string()));
+        assert!(!m.test(&"xs3:GetObject".to_string()));
+
+        let m = GlobMatcher::new("*:*Object");
+        assert!(m.test(&"s3:GetObject".to_string()));
+        assert!(m.test(&"s3:ObjectObject".to_string()));
+        assert!(!m.test(&"s3:GetObjects".to_string()));
+    }
+
+    #[test]
+    fn glob_matcher_one() {
+        let m = GlobMatcher::new("a?c");
+
+        assert!(m.test(&"abc".to_string()));
+        assert!(m.test(&"a?c".to_string()));
+        assert!(!m.test(&"ac".to_string()));
+        assert!(!m.test(&"abbc".to_string()));
+    }
+
+    #[test]
+    fn glob_matcher_escape() {
+        let m = GlobMatcher::new("a\\*");
+
+        assert!(m.test(&"a*".to_string()));
+        assert!(!m.test(&"ab".to_string()));
+    }
+
+    #[test]
+    fn glob_matcher_extended() {
+        let m = GlobMatcher::match_only("a?*".to_string());
+        assert!(m.test(&"a?*".to_string()));
+        assert!(!m.test(&"abc".to_string()));
+
+        assert!(GlobMatcher::match_any().test(&String::new()));
+        assert!(GlobMatcher::match_any().test(&"anything".to_string()));
+        assert!(!GlobMatcher::match_none().test(&String::new()));
+        assert!(!GlobMatcher::match_none().test(&"anything".to_string()));
+    }
+
+    #[test]
+    fn range_matcher_inclusive() {
+        let m = RangeMatcher::between(10, 20);
+
+        assert!(!m.test(&9));
+        assert!(m.test(&10));
+        assert!(m.test(&15));
+        assert!(m.test(&20));
+        assert!(!m.test(&21));
+    }
+
+    #[test]
+    fn range_matcher_exclusive() {
+        let m = RangeMatcher::new(Bound::Excluded(10), Bound::Excluded(20));
+
+        assert!(!m.test(&10));
+        assert!(m.test(&11));
+        assert!(m.test(&19));
+        assert!(!m.test(&20));
+    }
+
+    #[test]
+    fn range_matcher_half_open() {
+        let m = RangeMatcher::at_least(10);
+        assert!(!m.test(&9));
+        assert!(m.test(&10));
+        assert!(m.test(&i32::MAX));
+
+        let m = RangeMatcher::at_most(10);
+        assert!(m.test(&i32::MIN));
+        assert!(m.test(&10));
+        assert!(!m.test(&11));
+    }
+
+    #[test]
+    fn interval_set_matcher_boundaries() {
+        let m: IntervalSetMatcher<i32> = [
+            30..=40,
+            10..=20,
+            15..=25,
+            50..=50,
+            RangeInclusive::new(5, 1),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(m.intervals(), &[(10, 25), (30, 40), (50, 50)]);
+        assert!(!m.test(&9));
+        assert!(m.test(&10));
+        assert!(m.test(&25));
+        assert!(!m.test(&26));
+        assert!(!m.test(&29));
+        assert!(m.test(&30));
+        assert!(m.test(&40));
+        assert!(!m.test(&41));
+        assert!(!m.test(&49));
+        assert!(m.test(&50));
+        assert!(!m.test(&51));
+        assert!(!m.test(&3));
+    }
+
+    #[test]
+    fn interval_set_matcher_empty() {
+        let m = IntervalSetMatcher::<i32>::new([]);
+
+        assert!(m.intervals().is_empty());
+        assert!(!m.test(&0));
+    }
+
+    #[test]
+    fn interval_set_matcher_against_linear() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move |bound: u64| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) % bound
+        };
+        let ranges: Vec<RangeInclusive<u64>> = (0..300)
+            .map(|_| {
+                let start = next(20_000);
+                start..=start + next(50)
+            })
+            .collect();
+
+        let m = IntervalSetMatcher::new(ranges.clone());
+
+        for target in 0..20_100 {
+            assert_eq!(
+                m.test(&target),
+                ranges.iter().any(|r| r.contains(&target)),
+                "{}",
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn range_matcher_extended() {
+        let m = RangeMatcher::match_only(10);
+        assert!(!m.test(&9));
+        assert!(m.test(&10));
+        assert!(!m.test(&11));
+
+        let m = RangeMatcher::match_any();
+        assert!(m.test(&i32::MIN));
+        assert!(m.test(&0));
+        assert!(m.test(&i32::MAX));
+
+        let m = RangeMatcher::match_none();
+        assert!(!m.test(&i32::MIN));
+        assert!(!m.test(&0));
+        assert!(!m.test(&i32::MAX));
+    }
+
+    #[test]
+    fn one_of_matcher() {
+        let m: OneOfMatcher<_> = ["read", "list", "describe"].into_iter().collect();
+
+        assert!(m.test(&"read"));
+        assert!(m.test(&"list"));
+        assert!(m.test(&"describe"));
+        assert!(!m.test(&"write"));
+    }
+
+    #[test]
+    fn one_of_matcher_empty() {
+        let m: OneOfMatcher<&str> = [].into_iter().collect();
+
+        assert!(!m.test(&"read"));
+        assert_eq!(m, OneOfMatcher::match_none());
+    }
+
+    #[test]
+    fn one_of_matcher_single() {
+        let m = OneOfMatcher::match_only("read");
+
+        assert!(m.test(&"read"));
+        assert!(!m.test(&"write"));
+    }
+
+    #[test]
+    fn one_of_matcher_any() {
+        let m = OneOfMatcher::match_any();
+
+        assert!(m.test(&"read"));
+        assert!(m.test(&"write"));
+    }
+
+    #[test]
+    fn not_matcher() {
+        let m = NotMatcher(StrMatcher::match_only("a"));
+
+        assert!(!m.test(&"a"));
+        assert!(m.test(&"b"));
+    }
+
+    #[test]
+    fn any_of_matcher() {
+        let m: AnyOfMatcher<_> = ["a", "b"].into_iter().map(StrMatcher::from).collect();
+
+        assert!(m.test(&"a"));
+        assert!(m.test(&"b"));
+        assert!(!m.test(&"c"));
+    }
+
+    #[test]
+    fn all_of_matcher() {
+        let m = AllOfMatcher(vec![StrMatcher::match_any(), StrMatcher::match_only("a")]);
+        assert!(m.test(&"a"));
+        assert!(!m.test(&"b"));
+
+        let m = AllOfMatcher(vec![
+            StrMatcher::match_only("a"),
+            StrMatcher::match_only("b"),
+        ]);
+        assert!(!m.test(&"a"));
+        assert!(!m.test(&"b"));
+    }
+
+    #[test]
+    fn combinator_identities() {
+        assert!(AllOfMatcher::<StrMatcher>(vec![]).test(&"a"));
+        assert!(!AnyOfMatcher::<StrMatcher>(vec![]).test(&"a"));
+    }
+
+    #[test]
+    fn nested_combinators() {
+        let m = AllOfMatcher(vec![
+            AnyOfMatcher(vec![
+                StrMatcher::match_only("a"),
+                StrMatcher::match_only("b"),
+            ]),
+            AnyOfMatcher(vec![StrMatcher::match_any()]),
+        ]);
+        let m = NotMatcher(m);
+
+        assert!(!m.test(&"a"));
+        assert!(!m.test(&"b"));
+        assert!(m.test(&"c"));
+    }
+
+    #[test]
+    fn contramap_tuple() {
+        let m = StrMatcher::match_only("a").contramap(|t: &(&'static str, u32)| &t.0);
+
+        assert!(m.test(&("a", 1)));
+        assert!(!m.test(&("b", 1)));
+    }
+
+    #[test]
+    fn contramap_struct() {
+        struct Resource {
+            name: String,
+            region: String,
+        }
+
+        let by_name =
+            EqualityMatcher::match_only("doc".to_string()).contramap(|r: &Resource| &r.name);
+        let by_region =
+            EqualityMatcher::match_only("us".to_string()).contramap(|r: &Resource| &r.region);
+
+        let resource = Resource {
+            name: "doc".to_string(),
+            region: "eu".to_string(),
+        };
+
+        assert!(by_name.test(&resource));
+        assert!(!by_region.test(&resource));
+    }
+
+    #[test]
+    fn bloom_matcher_no_false_negatives() {
+        let m = BloomMatcher::with_members(0..10_000u32, 0.01);
+
+        assert_eq!(m.false_positive_rate(), 0.01);
+        assert!((0..10_000u32).all(|id| m.test(&id)));
+    }
+
+    #[test]
+    fn bloom_matcher_false_positive_rate() {
+        let m = BloomMatcher::with_members(0..10_000u32, 0.01);
+
+        let false_positives = (10_000..20_000u32).filter(|id| m.test(id)).count();
+
+        // expect about 100
+        assert!(false_positives < 200, "{}", false_positives);
+    }
+
+    #[test]
+    fn bloom_matcher_empty() {
+        let m = BloomMatcher::<&str>::new(100, 0.01);
+
+        assert!(!m.test(&"a"));
+    }
+
+    #[test]
+    fn pair_matcher() {
+        let m = PairMatcher::new(
+            StrMatcher::match_only("temp"),
+            StrMatcher::match_only("delete"),
+        );
+
+        for r in ["temp", "docs"] {
+            for a in ["delete", "read"] {
+                assert_eq!(m.test(&(r, a)), m.0.test(&r) && m.1.test(&a));
+            }
+        }
+        assert!(m.test(&("temp", "delete")));
+        assert!(!m.test(&("docs", "delete")));
+        assert!(!m.test(&("temp", "read")));
+    }
+
+    #[test]
+    fn dyn_matcher() {
+        let matchers: Vec<Box<dyn DynMatcher<String>>> = vec![
+            Box::new(EqualityMatcher::match_only("exact".to_string())),
+            Box::new(PrefixMatcher::new("docs/")),
+        ];
+        let any = AnyOfMatcher(matchers);
+
+        assert!(any.test(&"exact".to_string()));
+        assert!(any.test(&"docs/readme".to_string()));
+        assert!(!any.test(&"exactly".to_string()));
+        assert!(!any.test(&"images/logo".to_string()));
+    }
+
+    #[test]
+    fn fn_matcher() {
+        let m = FnMatcher::new(|n: &u32| n.is_multiple_of(2));
+
+        assert!(m.test(&4));
+        assert!(!m.test(&5));
+
+        let threshold = String::from("m");
+        let m = FnMatcher::new(move |s: &String| *s >= threshold);
+
+        assert!(m.test(&"x".to_string()));
+        assert!(!m.test(&"a".to_string()));
+    }
+
+    #[test]
+    fn fn_matcher_invokes_closure() {
+        let calls = std::cell::Cell::new(0);
+        let m = matcher(|s: &String| {
+            calls.set(calls.get() + 1);
+            s.len() < 10
+        });
+
+        assert!(m.test(&"short".to_string()));
+        assert!(!m.test(&"much too long".to_string()));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn fn_matcher_combinators() {
+        let short = || matcher(|s: &String| s.len() < 4);
+        let shouty = || matcher(|s: &String| s.chars().all(|c| c.is_ascii_uppercase()));
+
+        let long = NotMatcher(short());
+        assert!(long.test(&"lengthy".to_string()));
+        assert!(!long.test(&"abc".to_string()));
+
+        let either: AnyOfMatcher<Box<dyn DynMatcher<String>>> =
+            AnyOfMatcher(vec![Box::new(short()), Box::new(shouty())]);
+        assert!(either.test(&"abc".to_string()));
+        assert!(either.test(&"LENGTHY".to_string()));
+        assert!(!either.test(&"lengthy".to_string()));
+    }
+
+    #[test]
+    fn memo_matcher_tests_inner_once() {
+        struct Counting(std::cell::Cell<usize>);
+        impl Matcher for Counting {
+            type Target = &'static str;
+            fn test(&self, target: &Self::Target) -> bool {
+                self.0.set(self.0.get() + 1);
+                *target == "a"
+            }
+        }
+
+        let m = MemoMatcher::new(Counting(std::cell::Cell::new(0)));
+
+        assert!(m.test(&"a"));
+        assert!(m.test(&"a"));
+        assert!(!m.test(&"b"));
+        assert!(!m.test(&"b"));
+        assert!(m.test(&"a"));
+
+        assert_eq!(m.into_inner().0.get(), 2);
     }
 }
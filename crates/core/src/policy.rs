@@ -8,8 +8,10 @@
 //! the [definite effect](Effect) of matching some conditions.
 //!
 
-use std::iter::Filter;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
+use crate::condition::LogicExp;
 use crate::environment::Environment;
 
 use super::effect::*;
@@ -82,6 +84,11 @@ pub struct Policy2<SMatch, CExp>(Vec<Assertion2<SMatch, CExp>>);
 /// Authorization policy assertion.
 ///
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(from = "serde_repr::AssertionRepr<RMatch, AMatch, CExp>")
+)]
 /// Authorization poliicy primitve rule. Describes an effect of meeting
 /// resource, action, and environmental conditions.
 pub enum Assertion<RMatch, AMatch, CExp> {
@@ -112,12 +119,68 @@ impl<RMatch, AMatch, CExp> Assertion<RMatch, AMatch, CExp> {
         Assertion::Unconditional(RMatch::match_any(), AMatch::match_any(), Effect::ALLOW)
     }
 
-    pub fn for_subject(&self) -> SubjectAssertion<CExp> {
-        todo!();
+    /// Create an unconditional assertion from a joint resource and action matcher.
+    pub fn from_pair(subject: PairMatcher<RMatch, AMatch>, effect: Effect) -> Self {
+        let PairMatcher(rmatch, amatch) = subject;
+        Assertion::Unconditional(rmatch, amatch, effect)
+    }
+
+    /// Create a conditional assertion from a joint resource and action matcher.
+    pub fn from_pair_conditional(
+        subject: PairMatcher<RMatch, AMatch>,
+        effect: Effect,
+        condition: CExp,
+    ) -> Self {
+        let PairMatcher(rmatch, amatch) = subject;
+        Assertion::Conditional(rmatch, amatch, effect, condition)
+    }
+
+    /// The effect selected when the assertion applies.
+    pub fn effect(&self) -> Effect {
+        match self {
+            Assertion::Unconditional(_, _, eff) | Assertion::Conditional(_, _, eff, _) => *eff,
+        }
+    }
+
+    /// Key that totally orders assertions, first by variant (unconditional before
+    /// conditional), then by effect, resource matcher, action matcher and condition. Use it to
+    /// put assertions in a canonical order, e.g.
+    /// `assertions.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()))`.
+    pub fn sort_key(&self) -> (u8, Effect, &RMatch, &AMatch, Option<&CExp>)
+    where
+        RMatch: Ord,
+        AMatch: Ord,
+        CExp: Ord,
+    {
+        match self {
+            Assertion::Unconditional(rmatch, amatch, eff) => (0, *eff, rmatch, amatch, None),
+            Assertion::Conditional(rmatch, amatch, eff, exp) => {
+                (1, *eff, rmatch, amatch, Some(exp))
+            }
+        }
+    }
+
+    /// Reduce the assertion to its subject form, discarding the resource and action
+    /// matchers. Callers are expected to have already checked that it applies.
+    pub fn for_subject(&self) -> SubjectAssertion<CExp>
+    where
+        CExp: Clone,
+    {
+        match self {
+            Assertion::Unconditional(_, _, eff) => SubjectAssertion::Unconditional(*eff),
+            Assertion::Conditional(_, _, eff, exp) => {
+                SubjectAssertion::Conditional(*eff, exp.clone())
+            }
+        }
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Policy<As>(Vec<As>);
 
 impl<RMatch, AMatch, CExp> Policy<Assertion<RMatch, AMatch, CExp>> {
@@ -143,6 +206,85 @@ impl<RMatch, AMatch, CExp> Policy<Assertion<RMatch, AMatch, CExp>> {
         Assertion::allow_any().into()
     }
 
+    /// Lazily evaluate the policy for each subject. Nothing is evaluated until the
+    /// returned iterator is advanced, and only as many subjects as are consumed.
+    pub fn decisions<'a, R, A, Env, I>(
+        &'a self,
+        subjects: I,
+        environment: &'a Env,
+    ) -> impl Iterator<Item = ((R, A), ComputedEffect)> + 'a
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        Env: Environment<CExp = CExp>,
+        I: IntoIterator<Item = (R, A)>,
+        I::IntoIter: 'a,
+    {
+        subjects.into_iter().map(move |(resource, action)| {
+            let effect = self
+                .0
+                .iter()
+                .map(|assertion| assertion.apply(&resource, &action, environment))
+                .collect();
+            ((resource, action), effect)
+        })
+    }
+
+    /// Merge assertions that have identical matchers and conditions. Under deny-overrides
+    /// combination an ALLOW with the same scope as a DENY never contributes, so each
+    /// group collapses to a single assertion carrying the dominating effect. Merged
+    /// assertions take the position of the first member of their group.
+    pub fn reduce_duplicates(self) -> Self
+    where
+        RMatch: PartialEq,
+        AMatch: PartialEq,
+        CExp: PartialEq,
+    {
+        use Assertion::*;
+
+        let mut reduced: Vec<Assertion<RMatch, AMatch, CExp>> = Vec::with_capacity(self.0.len());
+        for assertion in self.0 {
+            let existing = reduced.iter_mut().find(|kept| match (&**kept, &assertion) {
+                (Unconditional(kr, ka, _), Unconditional(r, a, _)) => kr == r && ka == a,
+                (Conditional(kr, ka, _, kc), Conditional(r, a, _, c)) => {
+                    kr == r && ka == a && kc == c
+                }
+                _ => false,
+            });
+            match existing {
+                Some(Unconditional(_, _, eff) | Conditional(_, _, eff, _)) => {
+                    if assertion.effect() == Effect::DENY {
+                        *eff = Effect::DENY;
+                    }
+                }
+                None => reduced.push(assertion),
+            }
+        }
+        Policy(reduced)
+    }
+
+    /// Partition the policy into its allowing and denying assertions, in that order.
+    /// Relative order of assertions is preserved within each part.
+    pub fn split(self) -> (Self, Self) {
+        let (allows, denies) = self
+            .0
+            .into_iter()
+            .partition(|assertion| assertion.effect() == Effect::ALLOW);
+        (Policy(allows), Policy(denies))
+    }
+
+    /// Copy the allowing and denying assertions into separate policies, `(allows, denies)`,
+    /// preserving order within each. See [split](Policy::split) to partition without
+    /// copying.
+    pub fn partition_by_effect(&self) -> (Self, Self)
+    where
+        RMatch: Clone,
+        AMatch: Clone,
+        CExp: Clone,
+    {
+        self.clone().split()
+    }
+
     /// Supply an iterator over assertions that match the provided subject (resource and action).
     /// Matched policies are converted to SubjectPolicy's. The iterator supplies its results
     /// in arbitrary order.
@@ -150,7 +292,7 @@ impl<RMatch, AMatch, CExp> Policy<Assertion<RMatch, AMatch, CExp>> {
         &self,
         resource: &'a R,
         action: &'a A,
-    ) -> ForSubjectIter<'a, std::slice::Iter<Assertion<RMatch, AMatch, CExp>>, R, A>
+    ) -> ForSubjectIter<'a, std::slice::Iter<'_, Assertion<RMatch, AMatch, CExp>>, R, A>
     where
         RMatch: Matcher<Target = R>,
         AMatch: Matcher<Target = A>,
@@ -161,20 +303,491 @@ impl<RMatch, AMatch, CExp> Policy<Assertion<RMatch, AMatch, CExp>> {
             source: self.0.iter(),
         }
     }
+
+    /// Evaluate the policy for a subject in an environment. Applicable assertions are
+    /// resolved against the environment and combined so that deny overrides allow and
+    /// silence is the result when nothing applies.
+    pub fn evaluate<R, A, Env>(&self, resource: &R, action: &A, environment: &Env) -> ComputedEffect
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Clone,
+        Env: Environment<CExp = CExp>,
+    {
+        self.for_subject(resource, action)
+            .map(|assertion| assertion.resolve(environment))
+            .collect()
+    }
+
+    /// Determine if any action on a resource could be allowed in an environment. Access
+    /// exists when an applicable ALLOW assertion for the resource holds and no applicable
+    /// DENY assertion whose action matcher is the match-any matcher holds.
+    ///
+    /// This is an over-approximation. Denies on any other action matcher are ignored, even
+    /// when they cover every action the allows grant, so `true` means some action may be
+    /// allowed and [Policy::evaluate] decides whether a particular one is. `false` is exact.
+    pub fn has_any_access<R, Env>(&self, resource: &R, environment: &Env) -> bool
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: ExtendedMatcher + PartialEq,
+        Env: Environment<CExp = CExp>,
+    {
+        use Assertion::*;
+
+        let holding = |effect: Effect| {
+            self.0.iter().filter_map(move |assertion| match assertion {
+                Unconditional(rmatch, amatch, eff) if *eff == effect && rmatch.test(resource) => {
+                    Some(amatch)
+                }
+                Conditional(rmatch, amatch, eff, condition)
+                    if *eff == effect
+                        && rmatch.test(resource)
+                        && environment.evaluate(condition) =>
+                {
+                    Some(amatch)
+                }
+                _ => None,
+            })
+        };
+
+        let any_action = AMatch::match_any();
+        !holding(Effect::DENY).any(|amatch| *amatch == any_action)
+            && holding(Effect::ALLOW).next().is_some()
+    }
+
+    /// Evaluate the policy like [Policy::evaluate] while counting the work done. Action
+    /// matchers are only tested when the resource matches and conditions are only evaluated
+    /// for assertions that match the subject.
+    pub fn evaluate_with_stats<R, A, Env>(
+        &self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+    ) -> (ComputedEffect, ResolveStats)
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        Env: Environment<CExp = CExp>,
+    {
+        let mut stats = ResolveStats::default();
+        let effect = self
+            .0
+            .iter()
+            .map(|assertion| {
+                let (rmatch, amatch) = match assertion {
+                    Assertion::Unconditional(rmatch, amatch, _)
+                    | Assertion::Conditional(rmatch, amatch, _, _) => (rmatch, amatch),
+                };
+                stats.matcher_tests += 1;
+                if !rmatch.test(resource) {
+                    return SILENT;
+                }
+                stats.matcher_tests += 1;
+                if !amatch.test(action) {
+                    return SILENT;
+                }
+                match assertion {
+                    Assertion::Unconditional(_, _, eff) => eff.into(),
+                    Assertion::Conditional(_, _, eff, condition) => {
+                        stats.condition_evals += 1;
+                        if environment.evaluate(condition) {
+                            eff.into()
+                        } else {
+                            SILENT
+                        }
+                    }
+                }
+            })
+            .collect();
+        (effect, stats)
+    }
+
+    /// Evaluate the policy like [Policy::evaluate], refusing when more than `max_assertions`
+    /// assertions match the subject. Matching stops as soon as the bound is exceeded and no
+    /// conditions are evaluated in that case, which bounds the work done for a hostile policy.
+    pub fn evaluate_bounded<R, A, Env>(
+        &self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+        max_assertions: usize,
+    ) -> Result<ComputedEffect, EvalError>
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Clone,
+        Env: Environment<CExp = CExp>,
+    {
+        let matched: Vec<SubjectAssertion<CExp>> = self
+            .for_subject(resource, action)
+            .take(max_assertions.saturating_add(1))
+            .collect();
+        if matched.len() > max_assertions {
+            return Err(EvalError::TooLarge);
+        }
+        Ok(matched
+            .iter()
+            .map(|assertion| assertion.resolve(environment))
+            .collect())
+    }
+
+    /// Evaluate the policy like [Policy::evaluate] but stop at the first applicable deny,
+    /// skipping the remaining assertions and their conditions. This is only equivalent
+    /// because a deny overrides every other effect; it must not be used for combinations
+    /// where a later assertion could override a deny.
+    pub fn decide_fast<R, A, Env>(
+        &self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+    ) -> ComputedEffect
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Clone,
+        Env: Environment<CExp = CExp>,
+    {
+        let mut effect = SILENT;
+        for assertion in self.for_subject(resource, action) {
+            effect = [effect, assertion.resolve(environment)].iter().collect();
+            if effect == DENY {
+                return DENY;
+            }
+        }
+        effect
+    }
+
+    /// Evaluate the policy to a definite effect, using `default` when the policy is silent
+    /// for the subject.
+    pub fn decide<R, A, Env>(
+        &self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+        default: Effect,
+    ) -> Effect
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Clone,
+        Env: Environment<CExp = CExp>,
+    {
+        self.evaluate(resource, action, environment)
+            .effect()
+            .unwrap_or(default)
+    }
+
+    /// Evaluate using the deny-unless-permit combining algorithm. The result is ALLOW when
+    /// any applicable assertion allows and DENY otherwise. Unlike the default combination,
+    /// a permit is not overridden by an applicable deny and silence is never the result.
+    pub fn evaluate_deny_unless_permit<R, A, Env>(
+        &self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+    ) -> Effect
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        Env: Environment<CExp = CExp>,
+    {
+        let permitted = self.0.iter().any(|assertion| {
+            assertion.effect() == Effect::ALLOW && assertion.applies(resource, action, environment)
+        });
+        permitted.into()
+    }
+
+    /// The distinct conditions of the policy's conditional assertions, in the order they
+    /// first appear.
+    pub fn referenced_conditions(&self) -> Vec<&CExp>
+    where
+        CExp: Eq + Hash,
+    {
+        let mut seen = HashSet::new();
+        self.0
+            .iter()
+            .filter_map(|assertion| match assertion {
+                Assertion::Conditional(_, _, _, condition) => Some(condition),
+                Assertion::Unconditional(..) => None,
+            })
+            .filter(|condition| seen.insert(*condition))
+            .collect()
+    }
+
+    /// Find pairs of unconditional assertions with identical resource and action matchers
+    /// but opposing effects. The deny always wins so the allow of each pair is dead. Pairs
+    /// are positions in the policy, the earlier position first. Conditional assertions are
+    /// not considered since whether they conflict depends on the environment.
+    pub fn find_conflicts(&self) -> Vec<(usize, usize)>
+    where
+        RMatch: PartialEq,
+        AMatch: PartialEq,
+    {
+        let mut conflicts = Vec::new();
+        for (i, first) in self.0.iter().enumerate() {
+            for (j, second) in self.0.iter().enumerate().skip(i + 1) {
+                if let (
+                    Assertion::Unconditional(r1, a1, e1),
+                    Assertion::Unconditional(r2, a2, e2),
+                ) = (first, second)
+                {
+                    if e1 != e2 && r1 == r2 && a1 == a2 {
+                        conflicts.push((i, j));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Explain why a subject evaluates to silence in an environment. Silence results either
+    /// from no assertion matching the subject or from every matching assertion being
+    /// conditional on an expression that does not hold. Returns `None` when the policy is not
+    /// silent for the subject.
+    pub fn explain_silence<R, A, Env>(
+        &self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+    ) -> Option<SilenceCause<'_, CExp>>
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        Env: Environment<CExp = CExp>,
+    {
+        let mut matched = false;
+        let mut failed = Vec::new();
+        for assertion in self.0.iter() {
+            if !assertion.applies_to_subject(resource, action) {
+                continue;
+            }
+            matched = true;
+            match assertion {
+                Assertion::Conditional(_, _, _, condition) if !environment.evaluate(condition) => {
+                    failed.push(condition)
+                }
+                _ => return None,
+            }
+        }
+
+        if matched {
+            Some(SilenceCause::AllConditionsFalse(failed))
+        } else {
+            Some(SilenceCause::NoMatchingAssertions)
+        }
+    }
 }
 
-impl<RMatch, AMatch, CExp> FromIterator<Assertion<RMatch, AMatch, CExp>>
-    for Policy<Assertion<RMatch, AMatch, CExp>>
-{
-    fn from_iter<T: IntoIterator<Item = Assertion<RMatch, AMatch, CExp>>>(items: T) -> Self {
+impl<RMatch, AMatch, C> Policy<Assertion<RMatch, AMatch, LogicExp<C>>> {
+    /// Make every assertion in the policy depend on an additional condition, e.g. a
+    /// feature flag. Unconditional assertions become conditional on `gate` and existing
+    /// conditions are replaced by the conjunction of `gate` and the condition.
+    pub fn gated_by(self, gate: LogicExp<C>) -> Self
+    where
+        C: Clone,
+    {
+        self.0
+            .into_iter()
+            .map(|assertion| match assertion {
+                Assertion::Unconditional(rmatch, amatch, eff) => {
+                    Assertion::Conditional(rmatch, amatch, eff, gate.clone())
+                }
+                Assertion::Conditional(rmatch, amatch, eff, condition) => {
+                    Assertion::Conditional(rmatch, amatch, eff, gate.clone().and(condition))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Reason a policy could not be evaluated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    /// More assertions match the subject than allowed. See [Policy::evaluate_bounded].
+    TooLarge,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::TooLarge => write!(f, "too many assertions match the subject"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Work done while evaluating a policy. See [Policy::evaluate_with_stats].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ResolveStats {
+    /// Number of resource and action matcher tests
+    pub matcher_tests: usize,
+    /// Number of conditions evaluated in the environment
+    pub condition_evals: usize,
+}
+
+/// Reason a policy is silent for a subject. See [Policy::explain_silence].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SilenceCause<'a, CExp> {
+    /// No assertion matches the subject
+    NoMatchingAssertions,
+    /// Assertions match the subject but all of their conditions are false
+    AllConditionsFalse(Vec<&'a CExp>),
+}
+
+/// Evaluation plans memoized by the set of matched assertions for a single policy. The
+/// cache borrows the policy it is created for, so it cannot be used with another policy
+/// and the policy cannot change while the cache is alive.
+#[derive(Debug)]
+pub struct DecisionCache<'a, As> {
+    policy: &'a Policy<As>,
+    plans: HashMap<Vec<usize>, DecisionPlan>,
+}
+
+/// What remains to be decided for a set of matched assertions once their unconditional
+/// effects are combined. Conditions are kept as assertion positions and only those that can
+/// still change the outcome are kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DecisionPlan {
+    unconditional: ComputedEffect,
+    denies: Vec<usize>,
+    allows: Vec<usize>,
+}
+
+impl<'a, As> DecisionCache<'a, As> {
+    /// Create an empty cache for a policy.
+    pub fn new(policy: &'a Policy<As>) -> Self {
+        DecisionCache {
+            policy,
+            plans: HashMap::new(),
+        }
+    }
+
+    /// The policy whose decisions are cached.
+    pub fn policy(&self) -> &'a Policy<As> {
+        self.policy
+    }
+
+    /// Number of distinct sets of matched assertions cached.
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    /// Determine if nothing has been cached.
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    /// Discard all cached plans.
+    pub fn clear(&mut self) {
+        self.plans.clear()
+    }
+}
+
+impl<'a, RMatch, AMatch, CExp> DecisionCache<'a, Assertion<RMatch, AMatch, CExp>> {
+    /// Evaluate the policy for a subject in an environment like [Policy::evaluate]. Subjects
+    /// that match the same assertions share a plan of the conditions that can still change the
+    /// decision. A holding unconditional DENY decides without evaluating any condition, an
+    /// unconditional ALLOW leaves only conditional denies to evaluate, and evaluation stops
+    /// at the first conditional deny that holds.
+    pub fn evaluate<R, A, Env>(
+        &mut self,
+        resource: &R,
+        action: &A,
+        environment: &Env,
+    ) -> ComputedEffect
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        Env: Environment<CExp = CExp>,
+    {
+        let assertions = &self.policy.0;
+        let matched: Vec<usize> = assertions
+            .iter()
+            .enumerate()
+            .filter(|(_, assertion)| assertion.applies_to_subject(resource, action))
+            .map(|(i, _)| i)
+            .collect();
+
+        let plan = self.plans.entry(matched).or_insert_with_key(|matched| {
+            let mut plan = DecisionPlan {
+                unconditional: SILENT,
+                denies: Vec::new(),
+                allows: Vec::new(),
+            };
+            for &i in matched {
+                match &assertions[i] {
+                    Assertion::Unconditional(_, _, eff) => {
+                        plan.unconditional = [plan.unconditional, eff.into()].into_iter().collect()
+                    }
+                    Assertion::Conditional(_, _, Effect::DENY, _) => plan.denies.push(i),
+                    Assertion::Conditional(_, _, Effect::ALLOW, _) => plan.allows.push(i),
+                }
+            }
+            if plan.unconditional == DENY {
+                plan.denies.clear();
+            }
+            if plan.unconditional != SILENT {
+                plan.allows.clear();
+            }
+            plan
+        });
+
+        let holds = |&i: &usize| match &assertions[i] {
+            Assertion::Conditional(_, _, _, condition) => environment.evaluate(condition),
+            Assertion::Unconditional(..) => true,
+        };
+        if plan.unconditional == DENY || plan.denies.iter().any(holds) {
+            DENY
+        } else if plan.unconditional == ALLOW || plan.allows.iter().any(holds) {
+            ALLOW
+        } else {
+            SILENT
+        }
+    }
+}
+
+impl<As> Policy<As> {
+    /// Combine with another policy. Assertions from this policy come first.
+    pub fn merge(mut self, other: Policy<As>) -> Policy<As> {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Keep only the assertions for which `keep` is true, preserving order.
+    pub fn retain<F: FnMut(&As) -> bool>(&mut self, keep: F) {
+        self.0.retain(keep)
+    }
+
+    /// Transform every assertion, preserving order.
+    pub fn map_assertions<B, F: FnMut(As) -> B>(self, f: F) -> Policy<B> {
+        Policy(self.0.into_iter().map(f).collect())
+    }
+
+    /// Number of assertions in the policy.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<As> Extend<As> for Policy<As> {
+    fn extend<T: IntoIterator<Item = As>>(&mut self, items: T) {
+        self.0.extend(items)
+    }
+}
+
+impl<As> FromIterator<As> for Policy<As> {
+    fn from_iter<T: IntoIterator<Item = As>>(items: T) -> Self {
         Policy(items.into_iter().collect())
     }
 }
 
-impl<RMatch, AMatch, CExp> From<Vec<Assertion<RMatch, AMatch, CExp>>>
-    for Policy<Assertion<RMatch, AMatch, CExp>>
-{
-    fn from(items: Vec<Assertion<RMatch, AMatch, CExp>>) -> Self {
+impl<As> From<Vec<As>> for Policy<As> {
+    fn from(items: Vec<As>) -> Self {
         Policy(items)
     }
 }
@@ -187,8 +800,8 @@ impl<RMatch, AMatch, CExp> From<Assertion<RMatch, AMatch, CExp>>
     }
 }
 
-impl<RMatch, AMatch, CExp> IntoIterator for Policy<Assertion<RMatch, AMatch, CExp>> {
-    type Item = Assertion<RMatch, AMatch, CExp>;
+impl<As> IntoIterator for Policy<As> {
+    type Item = As;
 
     type IntoIter = <Vec<Self::Item> as IntoIterator>::IntoIter;
 
@@ -197,6 +810,16 @@ impl<RMatch, AMatch, CExp> IntoIterator for Policy<Assertion<RMatch, AMatch, CEx
     }
 }
 
+impl<'a, As> IntoIterator for &'a Policy<As> {
+    type Item = &'a As;
+
+    type IntoIter = std::slice::Iter<'a, As>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 impl<R, RMatch, A, AMatch, CExp> Assertion<RMatch, AMatch, CExp>
 where
     RMatch: Matcher<Target = R>,
@@ -263,46 +886,277 @@ where
     }
 }
 
+/// Assertion that additionally applies only to matching principals, e.g. "admins may
+/// delete".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrincipalAssertion<PMatch, RMatch, AMatch, CExp> {
+    /// Principals to which the assertion applies.
+    pub principal: PMatch,
+    /// Assertion applicable to the matched principals.
+    pub assertion: Assertion<RMatch, AMatch, CExp>,
+}
+
+impl<PMatch, RMatch, AMatch, CExp> PrincipalAssertion<PMatch, RMatch, AMatch, CExp> {
+    /// Scope an assertion to principals.
+    pub fn new(principal: PMatch, assertion: Assertion<RMatch, AMatch, CExp>) -> Self {
+        PrincipalAssertion {
+            principal,
+            assertion,
+        }
+    }
+
+    /// Determine if the assertion applies to a principal.
+    pub fn applies_to_principal<P>(&self, principal: &P) -> bool
+    where
+        PMatch: Matcher<Target = P>,
+    {
+        self.principal.test(principal)
+    }
+}
+
+impl<PMatch, RMatch, AMatch, CExp> Policy<PrincipalAssertion<PMatch, RMatch, AMatch, CExp>> {
+    pub fn iter(&self) -> impl Iterator<Item = &PrincipalAssertion<PMatch, RMatch, AMatch, CExp>> {
+        self.0.iter()
+    }
+
+    /// Supply an iterator over the assertions that apply to a principal, in policy order.
+    /// Collect them into a policy to evaluate subjects on behalf of the principal.
+    pub fn for_principal<'a, P>(
+        &'a self,
+        principal: &'a P,
+    ) -> impl Iterator<Item = &'a Assertion<RMatch, AMatch, CExp>> + 'a
+    where
+        PMatch: Matcher<Target = P>,
+    {
+        self.0
+            .iter()
+            .filter(move |pa| pa.applies_to_principal(principal))
+            .map(|pa| &pa.assertion)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(from = "serde_repr::SubjectAssertionRepr<CExp>")
+)]
 pub enum SubjectAssertion<CExp> {
     Unconditional(Effect),
     Conditional(Effect, CExp),
 }
 
-pub struct ForSubjectIter<'parm, Src, R, A> {
-    resource: &'parm R,
-    action: &'parm A,
-    source: Src,
-}
-
-impl<'param, RMatch, R, AMatch, A, CExp, Src> Iterator for ForSubjectIter<'param, Src, R, A>
-where
+impl<CExp> SubjectAssertion<CExp> {
+    /// Compute the effect of the assertion in an environment. A conditional assertion
+    /// whose condition does not hold is silent.
+    pub fn resolve<Env>(&self, environment: &Env) -> ComputedEffect
+    where
+        Env: Environment<CExp = CExp>,
+    {
+        match self {
+            SubjectAssertion::Unconditional(eff) => eff.into(),
+            SubjectAssertion::Conditional(eff, condition) => {
+                if environment.evaluate(condition) {
+                    eff.into()
+                } else {
+                    SILENT
+                }
+            }
+        }
+    }
+}
+
+pub struct ForSubjectIter<'parm, Src, R, A> {
+    resource: &'parm R,
+    action: &'parm A,
+    source: Src,
+}
+
+impl<'param, RMatch, R, AMatch, A, CExp, Src> Iterator for ForSubjectIter<'param, Src, R, A>
+where
     Src: Iterator<Item = &'param Assertion<RMatch, AMatch, CExp>> + 'param,
-    RMatch: Matcher<Target = R> + 'param + std::fmt::Debug,
-    AMatch: Matcher<Target = A> + 'param + std::fmt::Debug,
-    CExp: Clone + 'param + std::fmt::Debug,
+    RMatch: Matcher<Target = R> + 'param,
+    AMatch: Matcher<Target = A> + 'param,
+    CExp: Clone + 'param,
 {
     type Item = SubjectAssertion<CExp>;
 
     fn next(&mut self) -> Option<Self::Item> {
         for snext in self.source.by_ref() {
             if snext.applies_to_subject(self.resource, self.action) {
-                match snext {
-                    Assertion::Conditional(_, _, eff, exp) => {
-                        return Some(SubjectAssertion::Conditional(*eff, exp.clone()))
+                return Some(snext.for_subject());
+            }
+        }
+        None
+    }
+}
+
+/// Serialized forms of assertions. Variants are tagged with a `type` field and their
+/// components are named, e.g.
+/// `{"type":"conditional","resource":..,"action":..,"effect":"ALLOW","condition":..}`.
+#[cfg(feature = "serde")]
+mod serde_repr {
+    use serde::{Deserialize, Serialize, Serializer};
+
+    use super::{Assertion, Effect, SubjectAssertion};
+
+    #[derive(Serialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    enum AssertionRef<'a, RMatch, AMatch, CExp> {
+        Unconditional {
+            resource: &'a RMatch,
+            action: &'a AMatch,
+            effect: Effect,
+        },
+        Conditional {
+            resource: &'a RMatch,
+            action: &'a AMatch,
+            effect: Effect,
+            condition: &'a CExp,
+        },
+    }
+
+    #[derive(Deserialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    pub(super) enum AssertionRepr<RMatch, AMatch, CExp> {
+        Unconditional {
+            resource: RMatch,
+            action: AMatch,
+            effect: Effect,
+        },
+        Conditional {
+            resource: RMatch,
+            action: AMatch,
+            effect: Effect,
+            condition: CExp,
+        },
+    }
+
+    impl<RMatch, AMatch, CExp> Serialize for Assertion<RMatch, AMatch, CExp>
+    where
+        RMatch: Serialize,
+        AMatch: Serialize,
+        CExp: Serialize,
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Assertion::Unconditional(resource, action, effect) => {
+                    AssertionRef::Unconditional::<_, _, CExp> {
+                        resource,
+                        action,
+                        effect: *effect,
+                    }
+                }
+                Assertion::Conditional(resource, action, effect, condition) => {
+                    AssertionRef::Conditional {
+                        resource,
+                        action,
+                        effect: *effect,
+                        condition,
                     }
-                    Assertion::Unconditional(_, _, eff) => {
-                        return Some(SubjectAssertion::Unconditional(*eff))
-                    } // Assertion::Compound(_) => {
-                      //     panic!("Compound assertion is going away");
-                      // }
+                }
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<RMatch, AMatch, CExp> From<AssertionRepr<RMatch, AMatch, CExp>>
+        for Assertion<RMatch, AMatch, CExp>
+    {
+        fn from(repr: AssertionRepr<RMatch, AMatch, CExp>) -> Self {
+            match repr {
+                AssertionRepr::Unconditional {
+                    resource,
+                    action,
+                    effect,
+                } => Assertion::Unconditional(resource, action, effect),
+                AssertionRepr::Conditional {
+                    resource,
+                    action,
+                    effect,
+                    condition,
+                } => Assertion::Conditional(resource, action, effect, condition),
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    enum SubjectAssertionRef<'a, CExp> {
+        Unconditional { effect: Effect },
+        Conditional { effect: Effect, condition: &'a CExp },
+    }
+
+    #[derive(Deserialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    pub(super) enum SubjectAssertionRepr<CExp> {
+        Unconditional { effect: Effect },
+        Conditional { effect: Effect, condition: CExp },
+    }
+
+    impl<CExp> Serialize for SubjectAssertion<CExp>
+    where
+        CExp: Serialize,
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                SubjectAssertion::Unconditional(effect) => {
+                    SubjectAssertionRef::Unconditional { effect: *effect }
+                }
+                SubjectAssertion::Conditional(effect, condition) => {
+                    SubjectAssertionRef::Conditional {
+                        effect: *effect,
+                        condition,
+                    }
+                }
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<CExp> From<SubjectAssertionRepr<CExp>> for SubjectAssertion<CExp> {
+        fn from(repr: SubjectAssertionRepr<CExp>) -> Self {
+            match repr {
+                SubjectAssertionRepr::Unconditional { effect } => {
+                    SubjectAssertion::Unconditional(effect)
+                }
+                SubjectAssertionRepr::Conditional { effect, condition } => {
+                    SubjectAssertion::Conditional(effect, condition)
                 }
             }
         }
-        None
     }
 }
 
+/// Determine which of the supplied subjects are allowed by either of two policies. A
+/// subject is allowed by a policy when the policy's assertions combine to `ALLOW`.
+/// Subjects are returned in the order supplied.
+pub fn union_allowed<R, A, RMatch, AMatch, CExp, Env, I>(
+    a: &Policy<Assertion<RMatch, AMatch, CExp>>,
+    b: &Policy<Assertion<RMatch, AMatch, CExp>>,
+    subjects: I,
+    environment: &Env,
+) -> Vec<(R, A)>
+where
+    RMatch: Matcher<Target = R>,
+    AMatch: Matcher<Target = A>,
+    Env: Environment<CExp = CExp>,
+    I: IntoIterator<Item = (R, A)>,
+{
+    let allowed = |policy: &Policy<Assertion<RMatch, AMatch, CExp>>, resource: &R, action: &A| {
+        policy
+            .iter()
+            .map(|assertion| assertion.apply(resource, action, environment))
+            .collect::<ComputedEffect>()
+            == ALLOW
+    };
+
+    subjects
+        .into_iter()
+        .filter(|(resource, action)| allowed(a, resource, action) || allowed(b, resource, action))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -774,4 +1628,1063 @@ mod tests {
             .collect()
         );
     }
+
+    #[test]
+    fn test_assertion_for_subject() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let assertion: TestAssertion = Assertion::Unconditional(m_r, m_a, Effect::DENY);
+        let policy: TestPolicy = assertion.clone().into();
+        assert_eq!(
+            assertion.for_subject(),
+            SubjectAssertion::Unconditional(Effect::DENY)
+        );
+        assert_eq!(
+            policy.for_subject(&R, &A).collect::<Vec<_>>(),
+            vec![assertion.for_subject()]
+        );
+
+        let assertion: TestAssertion = Assertion::Conditional(m_r, m_a, Effect::ALLOW, true);
+        let policy: TestPolicy = assertion.clone().into();
+        assert_eq!(
+            assertion.for_subject(),
+            SubjectAssertion::Conditional(Effect::ALLOW, true)
+        );
+        assert_eq!(
+            policy.for_subject(&R, &A).collect::<Vec<_>>(),
+            vec![assertion.for_subject()]
+        );
+    }
+
+    #[test]
+    fn test_assertion_effect() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let assertion: TestAssertion = Assertion::Unconditional(m_r, m_a, Effect::ALLOW);
+        assert_eq!(assertion.effect(), Effect::ALLOW);
+        let assertion: TestAssertion = Assertion::Conditional(m_r, m_a, Effect::DENY, true);
+        assert_eq!(assertion.effect(), Effect::DENY);
+    }
+
+    #[test]
+    fn test_policy_split() {
+        let Matchers { m_r, m_r2, m_a, .. } = Matchers::new();
+
+        let terms = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+            Assertion::Conditional(m_r2, m_a, Effect::ALLOW, false),
+            Assertion::Unconditional(m_r2, m_a, Effect::DENY),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+        ];
+        let policy: TestPolicy = terms.clone().into();
+
+        let (allows, denies) = policy.split();
+
+        assert_eq!(
+            allows.into_iter().collect::<Vec<_>>(),
+            vec![terms[0].clone(), terms[2].clone()]
+        );
+        assert_eq!(
+            denies.into_iter().collect::<Vec<_>>(),
+            vec![terms[1].clone(), terms[3].clone(), terms[4].clone()]
+        );
+    }
+
+    #[test]
+    fn test_union_allowed() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+
+        let a: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r2, m_a2, Effect::DENY),
+        ]
+        .into_iter()
+        .collect();
+        let b: TestPolicy = [
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r2, m_a2, Effect::ALLOW),
+        ]
+        .into_iter()
+        .collect();
+        let subjects = [(R, A), (R, A2), (R2, A), (R2, A2)];
+
+        assert_eq!(
+            union_allowed(&a, &TestPolicy::from(vec![]), subjects, &TrivialEnv),
+            vec![(R, A)]
+        );
+        assert_eq!(
+            union_allowed(&TestPolicy::from(vec![]), &b, subjects, &TrivialEnv),
+            vec![(R2, A), (R2, A2)]
+        );
+        assert_eq!(
+            union_allowed(&a, &b, subjects, &TrivialEnv),
+            vec![(R, A), (R2, A), (R2, A2)]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        type JsonPolicy =
+            Policy<Assertion<EqualityMatcher<String>, EqualityMatcher<String>, String>>;
+
+        let policy: JsonPolicy = [
+            Assertion::Unconditional(
+                EqualityMatcher::match_only("r".to_string()),
+                EqualityMatcher::match_any(),
+                Effect::ALLOW,
+            ),
+            Assertion::Conditional(
+                EqualityMatcher::match_none(),
+                EqualityMatcher::match_only("a".to_string()),
+                Effect::DENY,
+                "c".to_string(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let json = serde_json::to_value(&policy).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"type": "unconditional", "resource": {"Only": "r"}, "action": "Any", "effect": "ALLOW"},
+                {"type": "conditional", "resource": "None", "action": {"Only": "a"}, "effect": "DENY", "condition": "c"},
+            ])
+        );
+
+        let actual: JsonPolicy = serde_json::from_value(json).unwrap();
+        assert_eq!(actual, policy);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_subject_assertion_round_trip() {
+        let assertions = vec![
+            SubjectAssertion::Unconditional(Effect::DENY),
+            SubjectAssertion::Conditional(Effect::ALLOW, 7),
+        ];
+
+        let json = serde_json::to_string(&assertions).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"type":"unconditional","effect":"DENY"},{"type":"conditional","effect":"ALLOW","condition":7}]"#
+        );
+
+        let actual: Vec<SubjectAssertion<u32>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual, assertions);
+    }
+
+    #[test]
+    fn test_for_principal() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+        let admins: OneOfMatcher<&str> = ["alice", "bob"].into_iter().collect();
+        let everyone = OneOfMatcher::match_any();
+
+        let read = Assertion::Unconditional(m_r, m_a, Effect::ALLOW);
+        let delete = Assertion::Unconditional(m_r, "delete".into(), Effect::ALLOW);
+        let policy: Policy<_> = [
+            PrincipalAssertion::new(everyone, read.clone()),
+            PrincipalAssertion::new(admins, delete.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        let alice: TestPolicy = policy.for_principal(&"alice").cloned().collect();
+        assert_eq!(alice, vec![read.clone(), delete].into());
+        assert_eq!(
+            alice.for_subject(&R, &"delete").collect::<Vec<_>>(),
+            vec![SubjectAssertion::Unconditional(Effect::ALLOW)]
+        );
+
+        let carol: TestPolicy = policy.for_principal(&"carol").cloned().collect();
+        assert_eq!(carol, read.into());
+        assert_eq!(carol.for_subject(&R, &"delete").count(), 0);
+    }
+
+    #[test]
+    fn test_reduce_duplicates() {
+        let Matchers { m_r, m_r2, m_a, .. } = Matchers::new();
+
+        let policy: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, false),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+        ]
+        .into_iter()
+        .collect();
+
+        let expected: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, false),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(policy.reduce_duplicates(), expected);
+    }
+
+    #[test]
+    fn test_reduce_duplicates_allow_deny_pair() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let policy: TestPolicy = [
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            policy.reduce_duplicates(),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true).into()
+        );
+    }
+
+    #[test]
+    fn test_has_any_access() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let policy: TestPolicy = [Assertion::Unconditional(m_r, m_a, Effect::ALLOW)]
+            .into_iter()
+            .collect();
+        assert!(policy.has_any_access(&R, &TrivialEnv));
+        assert!(!policy.has_any_access(&R2, &TrivialEnv));
+
+        let policy: TestPolicy = [Assertion::Conditional(m_r, m_a, Effect::ALLOW, false)]
+            .into_iter()
+            .collect();
+        assert!(!policy.has_any_access(&R, &TrivialEnv));
+
+        let policy: TestPolicy = [Assertion::Unconditional(m_r, m_a, Effect::DENY)]
+            .into_iter()
+            .collect();
+        assert!(!policy.has_any_access(&R, &TrivialEnv));
+
+        let policy: TestPolicy = TestPolicy::from(vec![]);
+        assert!(!policy.has_any_access(&R, &TrivialEnv));
+    }
+
+    #[test]
+    fn test_has_any_access_deny_interaction() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+        let any = StrMatcher::match_any();
+
+        // match-all deny overrides
+        let policy: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, any, Effect::DENY),
+        ]
+        .into_iter()
+        .collect();
+        assert!(!policy.has_any_access(&R, &TrivialEnv));
+
+        // match-all deny that does not hold does not override
+        let policy: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, any, Effect::DENY, false),
+        ]
+        .into_iter()
+        .collect();
+        assert!(policy.has_any_access(&R, &TrivialEnv));
+
+        // deny on another action does not
+        let policy: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+        ]
+        .into_iter()
+        .collect();
+        assert!(policy.has_any_access(&R, &TrivialEnv));
+
+        // neither does a deny covering every allowed action unless it is match-all, so the
+        // result over-approximates access
+        let policy: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+        ]
+        .into_iter()
+        .collect();
+        assert!(policy.has_any_access(&R, &TrivialEnv));
+        assert_eq!(policy.evaluate(&R, &A, &TrivialEnv), DENY);
+
+        let broad: OneOfMatcher<&'static str> = [A, A2].into_iter().collect();
+        let policy: Policy<Assertion<StrMatcher, OneOfMatcher<&'static str>, bool>> = [
+            Assertion::Unconditional(m_r, [A].into_iter().collect(), Effect::ALLOW),
+            Assertion::Unconditional(m_r, broad, Effect::DENY),
+        ]
+        .into_iter()
+        .collect();
+        assert!(policy.has_any_access(&R, &TrivialEnv));
+        assert_eq!(policy.evaluate(&R, &A, &TrivialEnv), DENY);
+    }
+
+    #[test]
+    fn test_iterate_borrowed() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+        let policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+        ]
+        .into();
+
+        let mut effects = Vec::new();
+        for assertion in &policy {
+            effects.push(assertion.effect());
+        }
+
+        assert_eq!(effects, vec![Effect::ALLOW, Effect::DENY]);
+        assert_eq!(policy.len(), 2);
+    }
+
+    #[test]
+    fn test_merge() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+        let specific = Assertion::Unconditional(m_r, m_a, Effect::DENY);
+
+        let merged = TestPolicy::allow_any().merge(specific.clone().into());
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(
+            merged.iter().collect::<Vec<_>>(),
+            vec![&TestAssertion::allow_any(), &specific]
+        );
+        assert_eq!(
+            merged.for_subject(&R, &A).collect::<Vec<_>>(),
+            vec![
+                SubjectAssertion::Unconditional(Effect::ALLOW),
+                SubjectAssertion::Unconditional(Effect::DENY),
+            ]
+        );
+        assert_eq!(merged.for_subject(&R2, &A).count(), 1);
+    }
+
+    #[test]
+    fn test_extend() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let mut policy = TestPolicy::from(vec![]);
+        assert!(policy.is_empty());
+
+        policy.extend([
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a2, Effect::DENY, true),
+        ]);
+
+        assert!(!policy.is_empty());
+        assert_eq!(policy.len(), 2);
+        assert_eq!(
+            policy.iter().next(),
+            Some(&Assertion::Unconditional(m_r, m_a, Effect::ALLOW))
+        );
+    }
+
+    #[test]
+    fn test_from_pair() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let assertion = TestAssertion::from_pair(PairMatcher(m_r, m_a), Effect::DENY);
+        assert_eq!(assertion, Assertion::Unconditional(m_r, m_a, Effect::DENY));
+        assert!(assertion.applies_to_subject(&R, &A));
+
+        let assertion =
+            TestAssertion::from_pair_conditional(PairMatcher(m_r, m_a), Effect::ALLOW, true);
+        assert_eq!(
+            assertion,
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true)
+        );
+    }
+
+    #[test]
+    fn test_retain() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let mut policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a2, Effect::DENY, true),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+            Assertion::Conditional(m_r, m_a2, Effect::ALLOW, false),
+        ]
+        .into();
+
+        policy
+            .retain(|assertion| !matches!(assertion, Assertion::Unconditional(_, _, Effect::DENY)));
+
+        assert_eq!(
+            policy,
+            vec![
+                Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+                Assertion::Conditional(m_r, m_a2, Effect::DENY, true),
+                Assertion::Conditional(m_r, m_a2, Effect::ALLOW, false),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_map_assertions() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a2, Effect::ALLOW, true),
+        ]
+        .into();
+
+        let denied = policy.map_assertions(|assertion| match assertion {
+            Assertion::Unconditional(r, a, _) => Assertion::Unconditional(r, a, Effect::DENY),
+            Assertion::Conditional(r, a, _, c) => Assertion::Conditional(r, a, Effect::DENY, c),
+        });
+
+        assert_eq!(
+            denied,
+            vec![
+                Assertion::Unconditional(m_r, m_a, Effect::DENY),
+                Assertion::Conditional(m_r, m_a2, Effect::DENY, true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_partition_by_effect() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+        let policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Conditional(m_r2, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r, m_a2, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+        ]
+        .into();
+
+        let (allows, denies) = policy.partition_by_effect();
+
+        assert_eq!(
+            allows,
+            vec![
+                Assertion::Conditional(m_r2, m_a, Effect::ALLOW, true),
+                Assertion::Unconditional(m_r, m_a2, Effect::ALLOW),
+            ]
+            .into()
+        );
+        assert_eq!(
+            denies,
+            vec![
+                Assertion::Unconditional(m_r, m_a, Effect::DENY),
+                Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+            ]
+            .into()
+        );
+
+        let sorted = |mut v: Vec<TestAssertion>| {
+            v.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+            v
+        };
+        let recombined: Vec<_> = allows.merge(denies).into_iter().collect();
+        assert_eq!(sorted(recombined), sorted(policy.into_iter().collect()));
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+
+        let assertions: Vec<TestAssertion> = vec![
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+            Assertion::Unconditional(m_r, m_a2, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+        ];
+        let sorted = |mut v: Vec<TestAssertion>| {
+            v.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+            v
+        };
+
+        let expected = sorted(assertions.clone());
+        assert_eq!(
+            expected[0],
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW)
+        );
+        assert_eq!(
+            expected[6],
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true)
+        );
+        for rotation in 1..assertions.len() {
+            let mut shuffled = assertions.clone();
+            shuffled.rotate_left(rotation);
+            shuffled.reverse();
+            assert_eq!(sorted(shuffled), expected);
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let allow: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+        ]
+        .into();
+        assert_eq!(allow.evaluate(&R, &A, &TrivialEnv), ALLOW);
+
+        let deny_overrides: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+        ]
+        .into();
+        assert_eq!(deny_overrides.evaluate(&R, &A, &TrivialEnv), DENY);
+
+        let silent: TestPolicy = vec![
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, false),
+            Assertion::Unconditional(m_r, m_a2, Effect::ALLOW),
+        ]
+        .into();
+        assert_eq!(silent.evaluate(&R, &A, &TrivialEnv), SILENT);
+        assert_eq!(
+            TestPolicy::from(vec![]).evaluate(&R, &A, &TrivialEnv),
+            SILENT
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with_stats() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+
+        let policy: TestPolicy = vec![
+            // resource misses: 1 test
+            Assertion::Conditional(m_r2, m_a, Effect::DENY, true),
+            // action misses: 2 tests
+            Assertion::Conditional(m_r, m_a2, Effect::DENY, true),
+            // matches: 2 tests
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            // matches: 2 tests, 1 condition
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+            // matches: 2 tests, 1 condition
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+        ]
+        .into();
+
+        let (effect, stats) = policy.evaluate_with_stats(&R, &A, &TrivialEnv);
+
+        assert_eq!(effect, policy.evaluate(&R, &A, &TrivialEnv));
+        assert_eq!(
+            stats,
+            ResolveStats {
+                matcher_tests: 9,
+                condition_evals: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decide_fast() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let policy: TestPolicy = vec![
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+        ]
+        .into();
+        let env = CountingEnv(std::cell::Cell::new(0));
+
+        assert_eq!(policy.decide_fast(&R, &A, &env), DENY);
+        assert_eq!(env.0.get(), 1);
+
+        env.0.set(0);
+        assert_eq!(policy.evaluate(&R, &A, &env), DENY);
+        assert_eq!(env.0.get(), 3);
+    }
+
+    #[test]
+    fn test_decide_fast_parity() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+        let assertions = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, false),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+        ];
+
+        for first in &assertions {
+            for second in &assertions {
+                let policy: TestPolicy = vec![first.clone(), second.clone()].into();
+                assert_eq!(
+                    policy.decide_fast(&R, &A, &TrivialEnv),
+                    policy.evaluate(&R, &A, &TrivialEnv)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_bounded() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+        let policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+        ]
+        .into();
+
+        assert_eq!(policy.evaluate_bounded(&R, &A, &TrivialEnv, 3), Ok(ALLOW));
+        assert_eq!(policy.evaluate_bounded(&R, &A, &TrivialEnv, 100), Ok(ALLOW));
+        assert_eq!(
+            policy.evaluate_bounded(&R, &A, &TrivialEnv, 2),
+            Err(EvalError::TooLarge)
+        );
+        assert_eq!(
+            TestPolicy::from(vec![]).evaluate_bounded(&R, &A, &TrivialEnv, 0),
+            Ok(SILENT)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_bounded_skips_conditions() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+        let policy: TestPolicy =
+            vec![Assertion::Conditional(m_r, m_a, Effect::ALLOW, true); 10].into();
+        let env = CountingEnv(std::cell::Cell::new(0));
+
+        assert_eq!(
+            policy.evaluate_bounded(&R, &A, &env, 5),
+            Err(EvalError::TooLarge)
+        );
+        assert_eq!(env.0.get(), 0);
+    }
+
+    #[test]
+    fn test_decide() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let allow: TestPolicy = Assertion::Unconditional(m_r, m_a, Effect::ALLOW).into();
+        assert_eq!(
+            allow.decide(&R, &A, &TrivialEnv, Effect::DENY),
+            Effect::ALLOW
+        );
+
+        let deny: TestPolicy = Assertion::Conditional(m_r, m_a, Effect::DENY, true).into();
+        assert_eq!(
+            deny.decide(&R, &A, &TrivialEnv, Effect::ALLOW),
+            Effect::DENY
+        );
+
+        let silent: TestPolicy = Assertion::Conditional(m_r, m_a, Effect::ALLOW, false).into();
+        assert_eq!(
+            silent.decide(&R, &A, &TrivialEnv, Effect::DENY),
+            Effect::DENY
+        );
+        assert_eq!(
+            silent.decide(&R, &A, &TrivialEnv, Effect::ALLOW),
+            Effect::ALLOW
+        );
+    }
+
+    #[test]
+    fn test_evaluate_deny_unless_permit() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let deny_unless_permit =
+            |policy: TestPolicy| policy.evaluate_deny_unless_permit(&R, &A, &TrivialEnv);
+
+        assert_eq!(deny_unless_permit(TestPolicy::from(vec![])), Effect::DENY);
+        assert_eq!(
+            deny_unless_permit(Assertion::Unconditional(m_r, m_a2, Effect::ALLOW).into()),
+            Effect::DENY
+        );
+        assert_eq!(
+            deny_unless_permit(Assertion::Conditional(m_r, m_a, Effect::ALLOW, false).into()),
+            Effect::DENY
+        );
+        assert_eq!(
+            deny_unless_permit(
+                vec![
+                    Assertion::Unconditional(m_r, m_a, Effect::DENY),
+                    Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+                ]
+                .into()
+            ),
+            Effect::DENY
+        );
+        assert_eq!(
+            deny_unless_permit(Assertion::Conditional(m_r, m_a, Effect::ALLOW, true).into()),
+            Effect::ALLOW
+        );
+        assert_eq!(
+            deny_unless_permit(
+                vec![
+                    Assertion::Unconditional(m_r, m_a, Effect::DENY),
+                    Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+                ]
+                .into()
+            ),
+            Effect::ALLOW
+        );
+    }
+
+    #[test]
+    fn test_referenced_conditions() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+
+        let policy: Policy<Assertion<StrMatcher, StrMatcher, &str>> = vec![
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, "weekday"),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+            Assertion::Conditional(m_r2, m_a, Effect::DENY, "on-call"),
+            Assertion::Conditional(m_r2, m_a2, Effect::ALLOW, "weekday"),
+            Assertion::Conditional(m_r, m_a2, Effect::ALLOW, "mfa"),
+        ]
+        .into();
+
+        assert_eq!(
+            policy.referenced_conditions(),
+            vec![&"weekday", &"on-call", &"mfa"]
+        );
+        assert!(TestPolicy::allow_any().referenced_conditions().is_empty());
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+
+        let policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Unconditional(m_r, m_a2, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+        ]
+        .into();
+
+        assert_eq!(policy.find_conflicts(), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn test_find_conflicts_none() {
+        let Matchers {
+            m_r,
+            m_r2,
+            m_a,
+            m_a2,
+            ..
+        } = Matchers::new();
+
+        let policy: TestPolicy = vec![
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r, m_a2, Effect::DENY),
+            Assertion::Unconditional(m_r2, m_a, Effect::DENY),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+        ]
+        .into();
+
+        assert!(policy.find_conflicts().is_empty());
+        assert!(TestPolicy::from(vec![]).find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_explain_silence_no_match() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let policy: TestPolicy = [Assertion::Unconditional(m_r, m_a, Effect::ALLOW)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            policy.explain_silence(&R2, &A, &TrivialEnv),
+            Some(SilenceCause::NoMatchingAssertions)
+        );
+        assert_eq!(
+            TestPolicy::from(vec![]).explain_silence(&R, &A, &TrivialEnv),
+            Some(SilenceCause::NoMatchingAssertions)
+        );
+    }
+
+    #[test]
+    fn test_explain_silence_conditions_false() {
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+
+        let policy: TestPolicy = [
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, false),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, false),
+            Assertion::Unconditional(m_r, m_a2, Effect::ALLOW),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            policy.explain_silence(&R, &A, &TrivialEnv),
+            Some(SilenceCause::AllConditionsFalse(vec![&false, &false]))
+        );
+    }
+
+    #[test]
+    fn test_explain_silence_not_silent() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let policy: TestPolicy = [
+            Assertion::Conditional(m_r, m_a, Effect::ALLOW, false),
+            Assertion::Conditional(m_r, m_a, Effect::DENY, true),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(policy.explain_silence(&R, &A, &TrivialEnv), None);
+    }
+
+    #[test]
+    fn test_subject_assertion_resolve() {
+        assert_eq!(
+            SubjectAssertion::Unconditional(Effect::ALLOW).resolve(&TrivialEnv),
+            ALLOW
+        );
+        assert_eq!(
+            SubjectAssertion::Unconditional(Effect::DENY).resolve(&TrivialEnv),
+            DENY
+        );
+        assert_eq!(
+            SubjectAssertion::Conditional(Effect::ALLOW, true).resolve(&TrivialEnv),
+            ALLOW
+        );
+        assert_eq!(
+            SubjectAssertion::Conditional(Effect::DENY, false).resolve(&TrivialEnv),
+            SILENT
+        );
+    }
+
+    #[test]
+    fn test_decisions() {
+        let Matchers { m_r, m_r2, m_a, .. } = Matchers::new();
+        let policy: TestPolicy = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Unconditional(m_r2, m_a, Effect::DENY),
+        ]
+        .into_iter()
+        .collect();
+
+        let actual: Vec<_> = policy
+            .decisions([(R, A), (R2, A), (R, A2)], &TrivialEnv)
+            .collect();
+
+        assert_eq!(
+            actual,
+            vec![((R, A), ALLOW), ((R2, A), DENY), ((R, A2), SILENT)]
+        );
+    }
+
+    /// Trivial environment that counts evaluations.
+    struct CountingEnv(std::cell::Cell<usize>);
+
+    impl Environment for CountingEnv {
+        type CExp = bool;
+        fn evaluate<Exp>(&self, exp: Exp) -> bool
+        where
+            Exp: std::borrow::Borrow<Self::CExp>,
+        {
+            self.0.set(self.0.get() + 1);
+            *exp.borrow()
+        }
+    }
+
+    #[test]
+    fn test_decisions_lazy() {
+        let policy: TestPolicy = Assertion::Conditional(
+            StrMatcher::match_any(),
+            StrMatcher::match_any(),
+            Effect::ALLOW,
+            true,
+        )
+        .into();
+        let env = CountingEnv(std::cell::Cell::new(0));
+        let subjects = std::iter::repeat((R, A));
+
+        let mut decisions = policy.decisions(subjects, &env);
+        assert_eq!(env.0.get(), 0);
+
+        let actual: Vec<_> = decisions.by_ref().take(3).collect();
+        assert_eq!(actual.len(), 3);
+        assert_eq!(env.0.get(), 3);
+    }
+
+    #[test]
+    fn test_gated_by() {
+        use crate::condition::LogicEnvironment;
+        use crate::environment::OverrideEnvironment;
+
+        let Matchers { m_r, m_a, m_a2, .. } = Matchers::new();
+        let policy: Policy<Assertion<_, _, LogicExp<&str>>> = [
+            Assertion::Unconditional(m_r, m_a, Effect::ALLOW),
+            Assertion::Conditional(m_r, m_a2, Effect::ALLOW, "weekday".into()),
+        ]
+        .into_iter()
+        .collect();
+        let gated = policy.gated_by("flag".into());
+
+        let decide = |env: &LogicEnvironment<_>, action| {
+            gated
+                .iter()
+                .map(|a| a.apply(&R, action, env))
+                .collect::<ComputedEffect>()
+        };
+
+        let flag_on = LogicEnvironment(OverrideEnvironment::new(
+            PositiveEnvironment::<&str>::default(),
+        ));
+        assert_eq!(decide(&flag_on, &A), ALLOW);
+        assert_eq!(decide(&flag_on, &A2), ALLOW);
+
+        let flag_on_weekend = LogicEnvironment(
+            OverrideEnvironment::new(PositiveEnvironment::<&str>::default()).with("weekday", false),
+        );
+        assert_eq!(decide(&flag_on_weekend, &A), ALLOW);
+        assert_eq!(decide(&flag_on_weekend, &A2), SILENT);
+
+        let flag_off = LogicEnvironment(
+            OverrideEnvironment::new(PositiveEnvironment::<&str>::default()).with("flag", false),
+        );
+        assert_eq!(decide(&flag_off, &A), SILENT);
+        assert_eq!(decide(&flag_off, &A2), SILENT);
+    }
+
+    #[test]
+    fn test_decision_cache_shares_shape() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        let policy: TestPolicy = [
+            Assertion::Conditional(StrMatcher::match_any(), m_a, Effect::ALLOW, true),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+        ]
+        .into_iter()
+        .collect();
+        let mut cache = DecisionCache::new(&policy);
+
+        let actual = cache.evaluate(&R2, &A, &TrivialEnv);
+        assert_eq!(actual, ALLOW);
+        assert_eq!(cache.len(), 1);
+
+        let actual = cache.evaluate(&"r3", &A, &TrivialEnv);
+        assert_eq!(actual, ALLOW);
+        assert_eq!(cache.len(), 1);
+
+        let actual = cache.evaluate(&R, &A, &TrivialEnv);
+        assert_eq!(actual, DENY);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_decision_cache_skips_conditions() {
+        let Matchers { m_r, m_r2, m_a, .. } = Matchers::new();
+        let any = StrMatcher::match_any();
+
+        let policy: TestPolicy = [
+            Assertion::Conditional(any, m_a, Effect::ALLOW, true),
+            Assertion::Conditional(any, m_a, Effect::DENY, false),
+            Assertion::Unconditional(m_r, m_a, Effect::DENY),
+            Assertion::Unconditional(m_r2, m_a, Effect::ALLOW),
+        ]
+        .into_iter()
+        .collect();
+        let env = CountingEnv(std::cell::Cell::new(0));
+        let mut cache = DecisionCache::new(&policy);
+
+        let mut check = |resource: &&'static str, expected, evaluations| {
+            env.0.set(0);
+            assert_eq!(cache.evaluate(resource, &A, &env), expected);
+            assert_eq!(env.0.get(), evaluations);
+
+            env.0.set(0);
+            assert_eq!(policy.evaluate(resource, &A, &env), expected);
+            assert_eq!(env.0.get(), 2);
+        };
+
+        // an unconditional deny decides, miss or hit
+        check(&R, DENY, 0);
+        check(&R, DENY, 0);
+        // an unconditional allow leaves only the conditional deny
+        check(&R2, ALLOW, 1);
+        // neither leaves both, and another subject with the same matches reuses the plan
+        check(&"r3", ALLOW, 2);
+        check(&"r4", ALLOW, 2);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_decision_cache_per_policy() {
+        let Matchers { m_r, m_a, .. } = Matchers::new();
+
+        // same shapes as each other, different decisions
+        let allow: TestPolicy = Assertion::Conditional(m_r, m_a, Effect::ALLOW, true).into();
+        let deny: TestPolicy = Assertion::Conditional(m_r, m_a, Effect::DENY, true).into();
+        let mut allow_cache = DecisionCache::new(&allow);
+        let mut deny_cache = DecisionCache::new(&deny);
+
+        assert_eq!(allow_cache.evaluate(&R, &A, &TrivialEnv), ALLOW);
+        assert_eq!(deny_cache.evaluate(&R, &A, &TrivialEnv), DENY);
+        assert_eq!(allow_cache.evaluate(&R, &A, &TrivialEnv), ALLOW);
+        assert!(std::ptr::eq(allow_cache.policy(), &allow));
+
+        // a changed policy needs a new cache, whose decisions follow the change
+        drop(allow_cache);
+        let mut changed = allow.clone();
+        changed.extend([Assertion::Unconditional(m_r, m_a, Effect::DENY)]);
+        let mut changed_cache = DecisionCache::new(&changed);
+        assert_eq!(changed_cache.evaluate(&R, &A, &TrivialEnv), DENY);
+        assert_eq!(
+            changed_cache.evaluate(&R, &A, &TrivialEnv),
+            changed.evaluate(&R, &A, &TrivialEnv)
+        );
+    }
 }
@@ -15,42 +15,102 @@ pub trait Template<T> {
     fn apply(self, p: &Self::Param) -> T;
 }
 
+/// Template that ignores its parameter and always generates the same value. Use it for
+/// policy components that do not vary with the parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixed<T, Param>(pub T, std::marker::PhantomData<fn(&Param)>);
+
+impl<T, Param> Fixed<T, Param> {
+    pub fn new(value: T) -> Self {
+        Fixed(value, std::marker::PhantomData)
+    }
+}
+
+impl<T, Param> From<T> for Fixed<T, Param> {
+    fn from(value: T) -> Self {
+        Fixed::new(value)
+    }
+}
+
+impl<T, Param> Template<T> for Fixed<T, Param> {
+    type Param = Param;
+
+    fn apply(self, _p: &Self::Param) -> T {
+        self.0
+    }
+}
+
 /// Template to generate a policy. It asumes that parameterization is equivalent to generating
-/// a resource matcher from a template and associating additional policy components as required
-/// by each variant.
+/// resource and action matchers and conditions from templates and associating additional
+/// policy components as required by each variant. All templates share the same parameter.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum PolicyTemplate<RMatchTpl, AMatch, CExp> {
-    /// Generates a `Policy::Unconditional(_)` by using a resource matcher template
-    Unconditional(RMatchTpl, AMatch, Effect),
-    /// generates a `Policy::Conditional(_)`  by using a resource matcher template
-    Conditional(RMatchTpl, AMatch, Effect, CExp),
-    /// generates a `Policy::Aggregate` by applying the parameter to all of its consituents
-    Aggregate(Vec<PolicyTemplate<RMatchTpl, AMatch, CExp>>),
+pub enum PolicyTemplate<RMatchTpl, AMatchTpl, CExpTpl> {
+    /// Generates an `Assertion::Unconditional(_)` by using resource and action matcher templates
+    Unconditional(RMatchTpl, AMatchTpl, Effect),
+    /// generates an `Assertion::Conditional(_)` by using resource and action matcher templates
+    /// and a condition template
+    Conditional(RMatchTpl, AMatchTpl, Effect, CExpTpl),
+    /// generates the assertions of all of its consituents by applying the parameter to each
+    Aggregate(Vec<PolicyTemplate<RMatchTpl, AMatchTpl, CExpTpl>>),
 }
 
-impl<Param, RMatchTpl, RMatch, AMatch, CExp> Template<Assertion<RMatch, AMatch, CExp>>
-    for PolicyTemplate<RMatchTpl, AMatch, CExp>
-where
-    RMatchTpl: Template<RMatch, Param = Param>,
-{
-    type Param = Param;
+/// Template that parameterizes only the resource matcher. The action matcher and condition
+/// are fixed when the template is constructed.
+pub type ResourcePolicyTemplate<RMatchTpl, AMatch, CExp, Param> =
+    PolicyTemplate<RMatchTpl, Fixed<AMatch, Param>, Fixed<CExp, Param>>;
 
-    fn apply(self, p: &Self::Param) -> Assertion<RMatch, AMatch, CExp> {
+impl<RMatchTpl, AMatchTpl, CExpTpl> PolicyTemplate<RMatchTpl, AMatchTpl, CExpTpl> {
+    fn apply_into<Param, RMatch, AMatch, CExp>(
+        self,
+        p: &Param,
+        assertions: &mut Vec<Assertion<RMatch, AMatch, CExp>>,
+    ) where
+        RMatchTpl: Template<RMatch, Param = Param>,
+        AMatchTpl: Template<AMatch, Param = Param>,
+        CExpTpl: Template<CExp, Param = Param>,
+    {
         use PolicyTemplate::*;
         match self {
-            Aggregate(_elems) => {
-                todo!()
-                // let policy = elems.into_iter().map(|e| e.apply(p)).collect();
-                // Assertion::Compound(policy)
-            }
-            Unconditional(rmtpl, am, eff) => Assertion::Unconditional(rmtpl.apply(p), am, eff),
-            Conditional(rmtpl, am, eff, cond) => {
-                Assertion::Conditional(rmtpl.apply(p), am, eff, cond)
+            Aggregate(elems) => {
+                for elem in elems {
+                    elem.apply_into(p, assertions);
+                }
             }
+            Unconditional(rmtpl, amtpl, eff) => assertions.push(Assertion::Unconditional(
+                rmtpl.apply(p),
+                amtpl.apply(p),
+                eff,
+            )),
+            Conditional(rmtpl, amtpl, eff, ctpl) => assertions.push(Assertion::Conditional(
+                rmtpl.apply(p),
+                amtpl.apply(p),
+                eff,
+                ctpl.apply(p),
+            )),
         }
     }
 }
 
+/// Templates generate policies. An `Unconditional` or `Conditional` template generates a
+/// policy of its single assertion. An `Aggregate` template generates a flat policy of the
+/// assertions of all of its constituents, in order, nested aggregates included.
+impl<Param, RMatchTpl, RMatch, AMatchTpl, AMatch, CExpTpl, CExp>
+    Template<Policy<Assertion<RMatch, AMatch, CExp>>>
+    for PolicyTemplate<RMatchTpl, AMatchTpl, CExpTpl>
+where
+    RMatchTpl: Template<RMatch, Param = Param>,
+    AMatchTpl: Template<AMatch, Param = Param>,
+    CExpTpl: Template<CExp, Param = Param>,
+{
+    type Param = Param;
+
+    fn apply(self, p: &Self::Param) -> Policy<Assertion<RMatch, AMatch, CExp>> {
+        let mut assertions = Vec::new();
+        self.apply_into(p, &mut assertions);
+        assertions.into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -77,85 +137,108 @@ mod tests {
         }
     }
 
-    // #[test]
-    // fn test_empty_aggregate() {
-    //     let template = PolicyTemplate::<RMatchTpl, AMatch, Cond>::Aggregate(vec![]);
-
-    //     let actual = template.apply(&"not important");
-
-    //     // assert_eq!(actual, Assertion::Compound(vec![]));
-    // }
-
-    // #[test]
-    // fn test_nonempty_aggregate() {
-    //     use PolicyTemplate::*;
-    //     let elems = vec![
-    //         Unconditional(RMatchTpl, AMatch("a1"), Effect::ALLOW),
-    //         Unconditional(RMatchTpl, AMatch("a2"), Effect::DENY),
-    //         Conditional(RMatchTpl, AMatch("a3"), Effect::ALLOW, Cond("c1")),
-    //         Conditional(RMatchTpl, AMatch("a4"), Effect::DENY, Cond("c2")),
-    //         Aggregate(vec![
-    //             Aggregate(vec![
-    //                 Unconditional(RMatchTpl, AMatch("a5"), Effect::ALLOW),
-    //                 Unconditional(RMatchTpl, AMatch("a6"), Effect::DENY),
-    //                 Conditional(RMatchTpl, AMatch("a7"), Effect::ALLOW, Cond("c3")),
-    //                 Conditional(RMatchTpl, AMatch("a8"), Effect::DENY, Cond("c4")),
-    //             ]),
-    //             Aggregate(vec![]),
-    //         ]),
-    //     ];
-    //     let template = Aggregate(elems.clone());
-
-    //     let actual = template.apply(&"param");
-
-    //     todo!()
-    //     // let expected = elems.into_iter().map(|e| e.apply(&"param")).collect();
-    //     // let expected = Assertion::Compound(expected);
-    //     // assert_eq!(actual, expected);
-    // }
+    #[derive(Clone, Copy)]
+    struct AMatchTpl;
+    impl Template<AMatch> for AMatchTpl {
+        type Param = &'static str;
+        fn apply(self, p: &Self::Param) -> AMatch {
+            AMatch(p)
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    struct CondTpl;
+    impl Template<Cond> for CondTpl {
+        type Param = &'static str;
+        fn apply(self, p: &Self::Param) -> Cond {
+            Cond(p)
+        }
+    }
+
+    type TestTemplate = ResourcePolicyTemplate<RMatchTpl, AMatch, Cond, &'static str>;
+
+    #[test]
+    fn test_empty_aggregate() {
+        let template = TestTemplate::Aggregate(vec![]);
+
+        let actual = template.apply(&"not important");
+
+        assert_eq!(actual, Policy::from(vec![]));
+    }
+
+    #[test]
+    fn test_nonempty_aggregate() {
+        use PolicyTemplate::*;
+        let a = |name| AMatch(name).into();
+        let c = |name| Cond(name).into();
+        let elems: Vec<TestTemplate> = vec![
+            Unconditional(RMatchTpl, a("a1"), Effect::ALLOW),
+            Unconditional(RMatchTpl, a("a2"), Effect::DENY),
+            Conditional(RMatchTpl, a("a3"), Effect::ALLOW, c("c1")),
+            Conditional(RMatchTpl, a("a4"), Effect::DENY, c("c2")),
+            Aggregate(vec![
+                Aggregate(vec![
+                    Unconditional(RMatchTpl, a("a5"), Effect::ALLOW),
+                    Unconditional(RMatchTpl, a("a6"), Effect::DENY),
+                    Conditional(RMatchTpl, a("a7"), Effect::ALLOW, c("c3")),
+                    Conditional(RMatchTpl, a("a8"), Effect::DENY, c("c4")),
+                ]),
+                Aggregate(vec![]),
+            ]),
+        ];
+        let template = Aggregate(elems);
+
+        let actual = template.apply(&"param");
+
+        let r = || RMatch("param");
+        let expected: Policy<Assertion<RMatch, AMatch, Cond>> = vec![
+            Assertion::Unconditional(r(), AMatch("a1"), Effect::ALLOW),
+            Assertion::Unconditional(r(), AMatch("a2"), Effect::DENY),
+            Assertion::Conditional(r(), AMatch("a3"), Effect::ALLOW, Cond("c1")),
+            Assertion::Conditional(r(), AMatch("a4"), Effect::DENY, Cond("c2")),
+            Assertion::Unconditional(r(), AMatch("a5"), Effect::ALLOW),
+            Assertion::Unconditional(r(), AMatch("a6"), Effect::DENY),
+            Assertion::Conditional(r(), AMatch("a7"), Effect::ALLOW, Cond("c3")),
+            Assertion::Conditional(r(), AMatch("a8"), Effect::DENY, Cond("c4")),
+        ]
+        .into();
+        assert_eq!(actual, expected);
+    }
 
     #[test]
     fn test_unconditional_allow() {
         let rmatch_tpl = RMatchTpl;
-        let template = PolicyTemplate::<RMatchTpl, AMatch, Cond>::Unconditional(
-            rmatch_tpl,
-            AMatch("a"),
-            Effect::ALLOW,
-        );
+        let template = TestTemplate::Unconditional(rmatch_tpl, AMatch("a").into(), Effect::ALLOW);
 
         let actual = template.apply(&"xyz");
 
         assert_eq!(
             actual,
-            Assertion::Unconditional(rmatch_tpl.apply(&"xyz"), AMatch("a"), Effect::ALLOW)
+            Assertion::Unconditional(rmatch_tpl.apply(&"xyz"), AMatch("a"), Effect::ALLOW).into()
         );
     }
 
     #[test]
     fn test_unconditional_deny() {
         let rmatch_tpl = RMatchTpl;
-        let template = PolicyTemplate::<RMatchTpl, AMatch, Cond>::Unconditional(
-            rmatch_tpl,
-            AMatch("a"),
-            Effect::DENY,
-        );
+        let template = TestTemplate::Unconditional(rmatch_tpl, AMatch("a").into(), Effect::DENY);
 
         let actual = template.apply(&"xyz");
 
         assert_eq!(
             actual,
-            Assertion::Unconditional(rmatch_tpl.apply(&"xyz"), AMatch("a"), Effect::DENY)
+            Assertion::Unconditional(rmatch_tpl.apply(&"xyz"), AMatch("a"), Effect::DENY).into()
         );
     }
 
     #[test]
     fn test_conditional_allow() {
         let rmatch_tpl = RMatchTpl;
-        let template = PolicyTemplate::<RMatchTpl, AMatch, Cond>::Conditional(
+        let template = TestTemplate::Conditional(
             rmatch_tpl,
-            AMatch("a"),
+            AMatch("a").into(),
             Effect::ALLOW,
-            Cond("c"),
+            Cond("c").into(),
         );
 
         let actual = template.apply(&"xyz");
@@ -168,17 +251,18 @@ mod tests {
                 Effect::ALLOW,
                 Cond("c")
             )
+            .into()
         );
     }
 
     #[test]
     fn test_conditional_deny() {
         let rmatch_tpl = RMatchTpl;
-        let template = PolicyTemplate::<RMatchTpl, AMatch, Cond>::Conditional(
+        let template = TestTemplate::Conditional(
             rmatch_tpl,
-            AMatch("a"),
+            AMatch("a").into(),
             Effect::DENY,
-            Cond("x"),
+            Cond("x").into(),
         );
 
         let actual = template.apply(&"xyz");
@@ -191,6 +275,49 @@ mod tests {
                 Effect::DENY,
                 Cond("x")
             )
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_fixed() {
+        let tpl: Fixed<_, &'static str> = AMatch("a").into();
+
+        assert_eq!(tpl.apply(&"xyz"), AMatch("a"));
+    }
+
+    #[test]
+    fn test_templated_action() {
+        let template = PolicyTemplate::Conditional(
+            RMatchTpl,
+            AMatchTpl,
+            Effect::ALLOW,
+            Fixed::<_, &'static str>::new(Cond("c")),
+        );
+
+        let actual: Policy<Assertion<RMatch, AMatch, Cond>> = template.apply(&"xyz");
+
+        assert_eq!(
+            actual,
+            Assertion::Conditional(RMatch("xyz"), AMatch("xyz"), Effect::ALLOW, Cond("c")).into()
+        );
+    }
+
+    #[test]
+    fn test_templated_condition() {
+        let template = PolicyTemplate::Conditional(RMatchTpl, AMatchTpl, Effect::DENY, CondTpl);
+
+        let actual: Policy<Assertion<RMatch, AMatch, Cond>> = template.apply(&"us-east");
+
+        assert_eq!(
+            actual,
+            Assertion::Conditional(
+                RMatch("us-east"),
+                AMatch("us-east"),
+                Effect::DENY,
+                Cond("us-east")
+            )
+            .into()
         );
     }
 }
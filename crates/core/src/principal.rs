@@ -5,6 +5,9 @@
 //! Can put initial ideas for federation and other autyhority-combining mechanisms here
 
 use crate::effect::*;
+use crate::environment::Environment;
+use crate::matcher::Matcher;
+use crate::policy::{Assertion, Policy};
 
 pub trait Authorized {
     fn authorized(&self) -> bool;
@@ -68,6 +71,8 @@ impl<A> Node<A> {
         }
     }
 
+    /// Iterate over the leaves in no particular order. Use
+    /// [leaves_in_order](Node::leaves_in_order) when order matters.
     pub fn iter(&self) -> impl Iterator<Item = &A> {
         struct Iter<'a, A>(Vec<&'a Node<A>>);
         impl<'a, A> Iterator for Iter<'a, A> {
@@ -88,6 +93,29 @@ impl<A> Node<A> {
         Iter(vec![&self])
     }
 
+    /// Iterate over the leaves from left to right.
+    pub fn leaves_in_order(&self) -> impl Iterator<Item = &A> {
+        struct Iter<'a, A>(Vec<&'a Node<A>>);
+        impl<'a, A> Iterator for Iter<'a, A> {
+            type Item = &'a A;
+            fn next(&mut self) -> Option<Self::Item> {
+                while let Some(node) = self.0.pop() {
+                    match node {
+                        Node::Leaf(a) => return Some(a),
+                        Node::And(l, r) | Node::Or(l, r) => {
+                            // right first so that left is popped first
+                            self.0.push(r);
+                            self.0.push(l);
+                        }
+                    }
+                }
+                None
+            }
+        }
+        Iter(vec![self])
+    }
+
+    /// Mutably iterate over the leaves in no particular order.
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut A> {
         struct Iter<'a, A>(Vec<&'a mut Node<A>>);
         impl<'a, A> Iterator for Iter<'a, A> {
@@ -110,24 +138,120 @@ impl<A> Node<A> {
     }
 }
 
+impl<A> Node<A> {
+    /// Evaluate the tree bottom-up, combining the values of the branches of each `And`
+    /// node with `and_fn` and of each `Or` node with `or_fn`.
+    pub fn eval_with<F, G>(self, and_fn: F, or_fn: G) -> A
+    where
+        F: Fn(A, A) -> A,
+        G: Fn(A, A) -> A,
+    {
+        self.fold(&and_fn, &or_fn)
+    }
+
+    fn fold<F, G>(self, and_fn: &F, or_fn: &G) -> A
+    where
+        F: Fn(A, A) -> A,
+        G: Fn(A, A) -> A,
+    {
+        match self {
+            Self::Leaf(a) => a,
+            Self::And(l, r) => and_fn(l.fold(and_fn, or_fn), r.fold(and_fn, or_fn)),
+            Self::Or(l, r) => or_fn(l.fold(and_fn, or_fn), r.fold(and_fn, or_fn)),
+        }
+    }
+}
+
 impl Node<ComputedEffect> {
+    /// Evaluate the tree. `And` requires both branches to allow; `Or` combines branches
+    /// like computed effects, ignoring silence and letting a denial override.
     pub fn eval(self) -> ComputedEffect {
+        self.eval_with(and_effects, or_effects)
+    }
+}
+
+impl<R, A> Node<(R, A)> {
+    /// Authorize a principal expression over subjects. Each leaf subject is evaluated
+    /// against the policy in the environment and the resulting tree of effects is
+    /// evaluated as in [eval](Node::eval).
+    pub fn authorize<RMatch, AMatch, CExp, Env>(
+        &self,
+        policy: &Policy<Assertion<RMatch, AMatch, CExp>>,
+        environment: &Env,
+    ) -> ComputedEffect
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Clone,
+        Env: Environment<CExp = CExp>,
+    {
+        self.effects(policy, environment).eval()
+    }
+
+    fn effects<RMatch, AMatch, CExp, Env>(
+        &self,
+        policy: &Policy<Assertion<RMatch, AMatch, CExp>>,
+        environment: &Env,
+    ) -> Node<ComputedEffect>
+    where
+        RMatch: Matcher<Target = R>,
+        AMatch: Matcher<Target = A>,
+        CExp: Clone,
+        Env: Environment<CExp = CExp>,
+    {
         match self {
-            Self::Leaf(a) => a,
-            Self::And(l, r) => match (l.eval(), r.eval()) {
-                (ALLOW, ALLOW) => ALLOW,
-                _ => DENY,
-            },
-            Self::Or(l, r) => match (l.eval(), r.eval()) {
-                (SILENT, r) => r,
-                (ALLOW, SILENT) => ALLOW,
-                (DENY, _) | (ALLOW, DENY) => DENY,
-                (ALLOW, ALLOW) => ALLOW,
-            },
+            Self::Leaf((resource, action)) => {
+                Node::Leaf(policy.evaluate(resource, action, environment))
+            }
+            Self::And(l, r) => l
+                .effects(policy, environment)
+                .and(r.effects(policy, environment)),
+            Self::Or(l, r) => l
+                .effects(policy, environment)
+                .or(r.effects(policy, environment)),
         }
     }
 }
 
+/// Conjunction of effects. Both branches must definitely allow. Both tables are total
+/// and symmetric.
+///
+/// | l \ r  | ALLOW | DENY | SILENT |
+/// |--------|-------|------|--------|
+/// | ALLOW  | ALLOW | DENY | DENY   |
+/// | DENY   | DENY  | DENY | DENY   |
+/// | SILENT | DENY  | DENY | DENY   |
+fn and_effects(l: ComputedEffect, r: ComputedEffect) -> ComputedEffect {
+    match (l, r) {
+        // both branches authorize
+        (ALLOW, ALLOW) => ALLOW,
+        // an explicit denial on either branch fails the conjunction
+        (DENY, _) | (_, DENY) => DENY,
+        // a silent branch is not an authorization, so the conjunction cannot be one;
+        // this includes (SILENT, SILENT)
+        (SILENT, _) | (_, SILENT) => DENY,
+    }
+}
+
+/// Disjunction of effects. Matches the combination of computed effects: silence
+/// contributes nothing and an explicit denial overrides an allow.
+///
+/// | l \ r  | ALLOW | DENY | SILENT |
+/// |--------|-------|------|--------|
+/// | ALLOW  | ALLOW | DENY | ALLOW  |
+/// | DENY   | DENY  | DENY | DENY   |
+/// | SILENT | ALLOW | DENY | SILENT |
+fn or_effects(l: ComputedEffect, r: ComputedEffect) -> ComputedEffect {
+    match (l, r) {
+        // a silent branch does not apply so the other decides; (SILENT, SILENT) stays
+        // silent and (DENY, SILENT) is the explicit denial
+        (SILENT, x) | (x, SILENT) => x,
+        // explicit denial overrides, including (ALLOW, DENY) and (DENY, ALLOW)
+        (DENY, _) | (_, DENY) => DENY,
+        (ALLOW, ALLOW) => ALLOW,
+    }
+}
+
 fn wot(a: bool) -> bool {
     !a
 }
@@ -194,6 +318,138 @@ mod tests {
         assert!(!Effect::DENY.silent());
     }
 
+    #[test]
+    fn test_leaves_in_order() {
+        let tree = Node::Leaf(1).and(Node::Leaf(2)).or(Node::Leaf(3));
+        assert_eq!(
+            tree.leaves_in_order().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let tree = Node::Leaf(1).or(Node::Leaf(2).and(Node::Leaf(3).or(Node::Leaf(4))));
+        assert_eq!(
+            tree.leaves_in_order().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        let tree = Node::Leaf(1);
+        assert_eq!(tree.leaves_in_order().copied().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn test_eval_and_table() {
+        fn check(l: ComputedEffect, r: ComputedEffect, expected: ComputedEffect) {
+            assert_eq!(
+                Node::from(l).and(r.into()).eval(),
+                expected,
+                "{l:?} AND {r:?}"
+            );
+        }
+
+        check(ALLOW, ALLOW, ALLOW);
+        check(ALLOW, DENY, DENY);
+        check(ALLOW, SILENT, DENY);
+        check(DENY, ALLOW, DENY);
+        check(DENY, DENY, DENY);
+        check(DENY, SILENT, DENY);
+        check(SILENT, ALLOW, DENY);
+        check(SILENT, DENY, DENY);
+        check(SILENT, SILENT, DENY);
+    }
+
+    #[test]
+    fn test_eval_or_table() {
+        fn check(l: ComputedEffect, r: ComputedEffect, expected: ComputedEffect) {
+            assert_eq!(
+                Node::from(l).or(r.into()).eval(),
+                expected,
+                "{l:?} OR {r:?}"
+            );
+        }
+
+        check(ALLOW, ALLOW, ALLOW);
+        check(ALLOW, DENY, DENY);
+        check(ALLOW, SILENT, ALLOW);
+        check(DENY, ALLOW, DENY);
+        check(DENY, DENY, DENY);
+        check(DENY, SILENT, DENY);
+        check(SILENT, ALLOW, ALLOW);
+        check(SILENT, DENY, DENY);
+        check(SILENT, SILENT, SILENT);
+    }
+
+    #[test]
+    fn test_eval_with_bool() {
+        let tree = Node::from(true).and(false.into()).or(true.into());
+        assert!(tree.eval_with(|l, r| l && r, |l, r| l || r));
+
+        let tree = Node::from(true).and(Node::from(false).or(false.into()));
+        assert!(!tree.eval_with(|l, r| l && r, |l, r| l || r));
+    }
+
+    #[test]
+    fn test_eval_with_sets() {
+        use std::collections::BTreeSet;
+
+        let set = |xs: &[u32]| Node::from(xs.iter().copied().collect::<BTreeSet<_>>());
+        let tree = set(&[1, 2, 3]).and(set(&[2, 3, 4])).or(set(&[9]));
+
+        let actual = tree.eval_with(
+            |l, r| l.intersection(&r).copied().collect(),
+            |l, r| l.union(&r).copied().collect(),
+        );
+
+        assert_eq!(actual, [2, 3, 9].into_iter().collect());
+    }
+
+    #[test]
+    fn test_authorize() {
+        use crate::environment::TrivialEnv;
+        use crate::matcher::EqualityMatcher;
+
+        let policy: Policy<Assertion<EqualityMatcher<&str>, EqualityMatcher<&str>, bool>> = vec![
+            Assertion::Unconditional("r1".into(), "a1".into(), Effect::ALLOW),
+            Assertion::Unconditional("r2".into(), "a2".into(), Effect::DENY),
+            Assertion::Conditional("r3".into(), "a3".into(), Effect::ALLOW, true),
+        ]
+        .into();
+
+        let both = Node::from(("r1", "a1")).and(("r2", "a2").into());
+        assert_eq!(both.authorize(&policy, &TrivialEnv), DENY);
+
+        let both = Node::from(("r1", "a1")).and(("r3", "a3").into());
+        assert_eq!(both.authorize(&policy, &TrivialEnv), ALLOW);
+
+        let either = Node::from(("r1", "a1")).or(("r4", "a4").into());
+        assert_eq!(either.authorize(&policy, &TrivialEnv), ALLOW);
+    }
+
+    #[test]
+    fn test_eval_effect_parity() {
+        let and_table = |l, r| match (l, r) {
+            (ALLOW, ALLOW) => ALLOW,
+            _ => DENY,
+        };
+        let or_table = |l, r| match (l, r) {
+            (SILENT, r) => r,
+            (ALLOW, SILENT) => ALLOW,
+            (DENY, _) | (ALLOW, DENY) => DENY,
+            (ALLOW, ALLOW) => ALLOW,
+        };
+        let tree = || {
+            Node::from(ALLOW)
+                .and(ALLOW.into())
+                .or(Node::from(SILENT).or(SILENT.into()))
+                .and(Node::from(SILENT).or(ALLOW.into()))
+        };
+
+        assert_eq!(tree().eval(), ALLOW);
+        assert_eq!(tree().eval(), tree().eval_with(and_table, or_table));
+
+        let tree = Node::from(ALLOW).or(DENY.into()).and(ALLOW.into());
+        assert_eq!(tree.eval(), DENY);
+    }
+
     // #[test]
     // fn test_combine_strict() {
     //     fn check<I>(effs: I, expected: ComputedEffect)